@@ -0,0 +1,388 @@
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection};
+use serde_json::json;
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::text::normalize_text;
+
+/// 句子卡片的笔记类型名称，名称不变时模型ID保持不变
+const MODEL_NAME: &str = "video2en sentence";
+
+/// Anki 卡片使用的音频来源
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnkiAudio {
+    /// 使用 `--clips` 切出的句子原声片段
+    Clip,
+    /// 使用 txt2audio 生成的男女声朗读
+    Tts,
+    /// 不带音频
+    None,
+}
+
+/// Anki 导出配置
+#[derive(Debug, Clone)]
+pub struct AnkiOptions {
+    /// 牌组名称，每个输入文件导出为其下的子牌组 `<deck>::<文件名>`
+    pub deck: String,
+    pub audio: AnkiAudio,
+    /// txt2audio 生成的 `<name>_audio_data.json`（`audio` 为 `Tts` 时使用）
+    pub tts_data: Option<PathBuf>,
+}
+
+impl Default for AnkiOptions {
+    fn default() -> Self {
+        Self {
+            deck: "video2en".to_string(),
+            audio: AnkiAudio::Clip,
+            tts_data: None,
+        }
+    }
+}
+
+/// 一张句子卡片
+#[derive(Debug, Clone)]
+pub struct AnkiNote {
+    pub english: String,
+    pub translation: String,
+    pub phonetic: String,
+    /// 来源文件和时间，显示在卡片背面
+    pub source: String,
+    /// 要打包进 .apkg 的音频文件
+    pub audio: Vec<AnkiMedia>,
+}
+
+/// 打包进 .apkg 的媒体文件
+#[derive(Debug, Clone)]
+pub struct AnkiMedia {
+    /// 在 Anki 媒体库中的文件名，不同牌组之间不能重名
+    pub name: String,
+    pub path: PathBuf,
+}
+
+impl AnkiNote {
+    /// 由规范化后的英文生成的稳定GUID，重复导入时更新已有笔记而不是新建
+    pub fn guid(&self) -> String {
+        format!("video2en-{:x}", md5::compute(normalize_text(&self.english)))
+    }
+}
+
+/// 一个 Anki 牌组，写出为 .apkg
+pub struct AnkiDeck {
+    pub name: String,
+    pub notes: Vec<AnkiNote>,
+}
+
+impl AnkiDeck {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            notes: Vec::new(),
+        }
+    }
+
+    /// 写出 .apkg：包含 `collection.anki2`（SQLite）、`media` 清单和编号的媒体文件
+    pub fn write_apkg(&self, path: &Path) -> Result<()> {
+        let temp_dir = tempfile::tempdir().context("Failed to create temporary directory")?;
+        let collection_path = temp_dir.path().join("collection.anki2");
+
+        // 规范化后相同的句子GUID相同，只保留第一张，否则写入时笔记ID冲突
+        let mut guids = HashSet::new();
+        let notes: Vec<&AnkiNote> = self.notes.iter().filter(|note| guids.insert(note.guid())).collect();
+
+        // 媒体文件在包内按序号存放，`media` 记录序号到文件名的映射
+        let mut media: Vec<(String, PathBuf)> = Vec::new();
+        let mut sound_fields = Vec::new();
+        for note in &notes {
+            let mut field = String::new();
+            for audio in &note.audio {
+                field.push_str(&format!("[sound:{}]", audio.name));
+                if !media.iter().any(|(name, _)| *name == audio.name) {
+                    media.push((audio.name.clone(), audio.path.clone()));
+                }
+            }
+            sound_fields.push(field);
+        }
+
+        self.write_collection(&collection_path, &notes, &sound_fields)?;
+
+        let file = fs::File::create(path).context(format!("Failed to create {}", path.display()))?;
+        let mut zip = zip::ZipWriter::new(file);
+        let options = zip::write::SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+
+        zip.start_file("collection.anki2", options)?;
+        zip.write_all(&fs::read(&collection_path).context("Failed to read Anki collection")?)?;
+
+        let media_map: HashMap<String, &str> = media
+            .iter()
+            .enumerate()
+            .map(|(i, (name, _))| (i.to_string(), name.as_str()))
+            .collect();
+        zip.start_file("media", options)?;
+        zip.write_all(serde_json::to_string(&media_map)?.as_bytes())?;
+
+        for (i, (_, audio)) in media.iter().enumerate() {
+            zip.start_file(i.to_string(), options)?;
+            zip.write_all(&fs::read(audio).context(format!("Failed to read audio: {}", audio.display()))?)?;
+        }
+
+        zip.finish().context(format!("Failed to write {}", path.display()))?;
+        Ok(())
+    }
+
+    fn write_collection(&self, path: &Path, notes: &[&AnkiNote], sound_fields: &[String]) -> Result<()> {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+        let (now_secs, now_ms) = (now.as_secs() as i64, now.as_millis() as i64);
+        let model_id = stable_id(MODEL_NAME);
+        let deck_id = stable_id(&self.name);
+
+        let conn = Connection::open(path).context("Failed to create Anki collection")?;
+        conn.execute_batch(COLLECTION_SCHEMA)?;
+        conn.execute(
+            "INSERT INTO col VALUES (1, ?1, ?2, ?2, 11, 0, 0, 0, ?3, ?4, ?5, ?6, '{}')",
+            params![
+                now_secs,
+                now_ms,
+                collection_conf(deck_id, model_id).to_string(),
+                json!({ model_id.to_string(): note_model(model_id, deck_id, now_secs) }).to_string(),
+                json!({
+                    "1": deck_json(1, "Default", now_secs),
+                    deck_id.to_string(): deck_json(deck_id, &self.name, now_secs),
+                })
+                .to_string(),
+                json!({ "1": deck_conf() }).to_string(),
+            ],
+        )?;
+
+        for (i, (note, sound)) in notes.iter().zip(sound_fields).enumerate() {
+            let guid = note.guid();
+            let note_id = stable_id(&guid);
+            let fields = [
+                escape_html(&note.english),
+                escape_html(&note.translation),
+                escape_html(&note.phonetic),
+                sound.clone(),
+                escape_html(&note.source),
+            ];
+
+            conn.execute(
+                "INSERT INTO notes VALUES (?1, ?2, ?3, ?4, -1, '', ?5, ?6, ?7, 0, '')",
+                params![note_id, guid, model_id, now_secs, fields.join("\x1f"), note.english, checksum(&note.english)],
+            )?;
+            conn.execute(
+                "INSERT INTO cards VALUES (?1, ?2, ?3, 0, ?4, -1, 0, 0, ?5, 0, 0, 0, 0, 0, 0, 0, 0, '')",
+                params![stable_id(&format!("{}-card", guid)), note_id, deck_id, now_secs, i as i64 + 1],
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// 由名称生成稳定的ID，限制在 2^52 以内以便 Anki 在各平台安全使用
+fn stable_id(name: &str) -> i64 {
+    let digest = md5::compute(name);
+    let value = i64::from_be_bytes(digest.0[..8].try_into().unwrap());
+    (value & ((1 << 52) - 1)).max(2)
+}
+
+/// Anki 用于查重的字段校验和：首字段 SHA1 的前8位十六进制
+fn checksum(text: &str) -> i64 {
+    let digest = sha1_smol::Sha1::from(text).digest().to_string();
+    i64::from_str_radix(&digest[..8], 16).unwrap_or_default()
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+fn collection_conf(deck_id: i64, model_id: i64) -> serde_json::Value {
+    json!({
+        "activeDecks": [deck_id],
+        "curDeck": deck_id,
+        "curModel": model_id,
+        "newSpread": 0,
+        "collapseTime": 1200,
+        "timeLim": 0,
+        "estTimes": true,
+        "dueCounts": true,
+        "nextPos": 1,
+        "sortType": "noteFld",
+        "sortBackwards": false,
+        "addToCur": true,
+    })
+}
+
+fn note_model(model_id: i64, deck_id: i64, now_secs: i64) -> serde_json::Value {
+    let field = |name: &str, ord: usize| {
+        json!({ "name": name, "ord": ord, "sticky": false, "rtl": false, "font": "Arial", "size": 20, "media": [] })
+    };
+    json!({
+        "id": model_id,
+        "name": MODEL_NAME,
+        "type": 0,
+        "mod": now_secs,
+        "usn": -1,
+        "sortf": 0,
+        "did": deck_id,
+        "tags": [],
+        "vers": [],
+        "flds": [
+            field("English", 0),
+            field("Translation", 1),
+            field("Phonetic", 2),
+            field("Audio", 3),
+            field("Source", 4),
+        ],
+        "tmpls": [{
+            "name": "Listen & Read",
+            "ord": 0,
+            "qfmt": "<div class=\"english\">{{English}}</div>\n{{Audio}}",
+            "afmt": "{{FrontSide}}\n<hr id=\"answer\">\n<div class=\"phonetic\">{{Phonetic}}</div>\n\
+                     <div class=\"translation\">{{Translation}}</div>\n<div class=\"source\">{{Source}}</div>",
+            "did": null,
+            "bqfmt": "",
+            "bafmt": "",
+        }],
+        "css": ".card { font-family: arial; font-size: 24px; text-align: center; color: black; background-color: white; }\n\
+                .phonetic { color: #888; }\n.translation { color: #d35400; margin-top: 12px; }\n\
+                .source { color: #aaa; font-size: 14px; margin-top: 24px; }",
+        "latexPre": "\\documentclass[12pt]{article}\n\\special{papersize=3in,5in}\n\\usepackage{amssymb,amsmath}\n\
+                     \\pagestyle{empty}\n\\setlength{\\parindent}{0in}\n\\begin{document}\n",
+        "latexPost": "\\end{document}",
+        "req": [[0, "any", [0]]],
+    })
+}
+
+fn deck_json(deck_id: i64, name: &str, now_secs: i64) -> serde_json::Value {
+    json!({
+        "id": deck_id,
+        "name": name,
+        "desc": "",
+        "mod": now_secs,
+        "usn": -1,
+        "collapsed": false,
+        "browserCollapsed": false,
+        "newToday": [0, 0],
+        "revToday": [0, 0],
+        "lrnToday": [0, 0],
+        "timeToday": [0, 0],
+        "dyn": 0,
+        "extendNew": 10,
+        "extendRev": 50,
+        "conf": 1,
+    })
+}
+
+fn deck_conf() -> serde_json::Value {
+    json!({
+        "id": 1,
+        "name": "Default",
+        "mod": 0,
+        "usn": 0,
+        "maxTaken": 60,
+        "autoplay": true,
+        "timer": 0,
+        "replayq": true,
+        "dyn": false,
+        "new": { "bury": true, "delays": [1.0, 10.0], "initialFactor": 2500, "ints": [1, 4, 7], "order": 1, "perDay": 20, "separate": true },
+        "lapse": { "delays": [10.0], "leechAction": 0, "leechFails": 8, "minInt": 1, "mult": 0.0 },
+        "rev": { "bury": true, "ease4": 1.3, "fuzz": 0.05, "ivlFct": 1.0, "maxIvl": 36500, "minSpace": 1, "perDay": 100 },
+    })
+}
+
+const COLLECTION_SCHEMA: &str = "
+CREATE TABLE col (
+    id integer primary key, crt integer not null, mod integer not null, scm integer not null,
+    ver integer not null, dty integer not null, usn integer not null, ls integer not null,
+    conf text not null, models text not null, decks text not null, dconf text not null, tags text not null
+);
+CREATE TABLE notes (
+    id integer primary key, guid text not null, mid integer not null, mod integer not null,
+    usn integer not null, tags text not null, flds text not null, sfld integer not null,
+    csum integer not null, flags integer not null, data text not null
+);
+CREATE TABLE cards (
+    id integer primary key, nid integer not null, did integer not null, ord integer not null,
+    mod integer not null, usn integer not null, type integer not null, queue integer not null,
+    due integer not null, ivl integer not null, factor integer not null, reps integer not null,
+    lapses integer not null, left integer not null, odue integer not null, odid integer not null,
+    flags integer not null, data text not null
+);
+CREATE TABLE revlog (
+    id integer primary key, cid integer not null, usn integer not null, ease integer not null,
+    ivl integer not null, lastIvl integer not null, factor integer not null, time integer not null,
+    type integer not null
+);
+CREATE TABLE graves (usn integer not null, oid integer not null, type integer not null);
+CREATE INDEX ix_notes_usn on notes (usn);
+CREATE INDEX ix_cards_usn on cards (usn);
+CREATE INDEX ix_revlog_usn on revlog (usn);
+CREATE INDEX ix_cards_nid on cards (nid);
+CREATE INDEX ix_cards_sched on cards (did, queue, due);
+CREATE INDEX ix_revlog_cid on revlog (cid);
+CREATE INDEX ix_notes_csum on notes (csum);
+";
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::io::Read;
+
+    #[test]
+    fn test_write_apkg() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("0001.wav");
+        fs::write(&path, b"RIFF").unwrap();
+
+        let mut deck = AnkiDeck::new("video2en::movie");
+        deck.notes.push(AnkiNote {
+            english: "Take it easy.".to_string(),
+            translation: "放轻松".to_string(),
+            phonetic: "teɪk".to_string(),
+            source: "movie.mp4 00:00:01".to_string(),
+            audio: vec![AnkiMedia {
+                name: "movie_0001.wav".to_string(),
+                path,
+            }],
+        });
+        // 规范化后相同的句子只导出一张卡片
+        deck.notes.push(AnkiNote {
+            english: "Take it easy!".to_string(),
+            audio: vec![AnkiMedia {
+                name: "movie_0002.wav".to_string(),
+                path: dir.path().join("0002.wav"),
+            }],
+            ..deck.notes[0].clone()
+        });
+        let apkg = dir.path().join("movie.apkg");
+        deck.write_apkg(&apkg).unwrap();
+
+        let mut archive = zip::ZipArchive::new(fs::File::open(&apkg).unwrap()).unwrap();
+        let mut media = String::new();
+        archive.by_name("media").unwrap().read_to_string(&mut media).unwrap();
+        assert_eq!(media, r#"{"0":"movie_0001.wav"}"#);
+
+        let collection = dir.path().join("collection.anki2");
+        let mut bytes = Vec::new();
+        archive.by_name("collection.anki2").unwrap().read_to_end(&mut bytes).unwrap();
+        fs::write(&collection, bytes).unwrap();
+
+        let conn = Connection::open(&collection).unwrap();
+        let (guid, flds): (String, String) = conn
+            .query_row("SELECT guid, flds FROM notes", [], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap();
+        // 大小写和标点不同的同一句子得到相同GUID
+        let same = AnkiNote {
+            english: "take it easy".to_string(),
+            ..deck.notes[0].clone()
+        };
+        assert_eq!(guid, same.guid());
+        assert_eq!(flds, "Take it easy.\x1f放轻松\x1fteɪk\x1f[sound:movie_0001.wav]\x1fmovie.mp4 00:00:01");
+    }
+}
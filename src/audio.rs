@@ -0,0 +1,92 @@
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path};
+
+/// Whisper 要求的采样率
+pub const WHISPER_SAMPLE_RATE: u32 = 16000;
+
+/// txt2audio 为一行文本生成的男女声音频
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AudioEntry {
+    pub text: String,
+    pub female_audio: String,
+    pub male_audio: String,
+    pub line_number: usize,
+}
+
+/// txt2audio 输出的 `<name>_audio_data.json`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AudioData {
+    pub entries: Vec<AudioEntry>,
+    pub total_count: usize,
+    pub output_directory: String,
+    pub input_file: String,
+}
+
+impl AudioData {
+    pub fn read(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .context(format!("Failed to read audio data: {}", path.display()))?;
+        serde_json::from_str(&content).context(format!("Invalid audio data: {}", path.display()))
+    }
+}
+
+/// 读取16kHz WAV文件为单声道f32采样（多声道取平均）
+pub fn read_wav_samples(path: &Path) -> Result<Vec<f32>> {
+    let mut reader = hound::WavReader::open(path)
+        .context(format!("Failed to open WAV file: {}", path.display()))?;
+    let spec = reader.spec();
+
+    if spec.sample_rate != WHISPER_SAMPLE_RATE {
+        return Err(anyhow!(
+            "Unsupported sample rate {} in {}, expected {}",
+            spec.sample_rate,
+            path.display(),
+            WHISPER_SAMPLE_RATE
+        ));
+    }
+
+    let samples: Vec<f32> = match spec.sample_format {
+        hound::SampleFormat::Float => reader
+            .samples::<f32>()
+            .collect::<Result<_, _>>()
+            .context("Failed to read WAV samples")?,
+        hound::SampleFormat::Int => {
+            let scale = (1i64 << (spec.bits_per_sample - 1)) as f32;
+            reader
+                .samples::<i32>()
+                .map(|sample| sample.map(|s| s as f32 / scale))
+                .collect::<Result<_, _>>()
+                .context("Failed to read WAV samples")?
+        }
+    };
+
+    let channels = spec.channels.max(1) as usize;
+    if channels == 1 {
+        return Ok(samples);
+    }
+
+    Ok(samples
+        .chunks(channels)
+        .map(|frame| frame.iter().sum::<f32>() / frame.len() as f32)
+        .collect())
+}
+
+/// 把单声道f32采样写为16位PCM WAV
+pub fn write_wav_samples(path: &Path, samples: &[f32], sample_rate: u32) -> Result<()> {
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut writer = hound::WavWriter::create(path, spec)
+        .context(format!("Failed to create WAV file: {}", path.display()))?;
+    for sample in samples {
+        writer
+            .write_sample((sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16)
+            .context("Failed to write WAV samples")?;
+    }
+    writer.finalize().context(format!("Failed to finalize WAV file: {}", path.display()))?;
+    Ok(())
+}
@@ -1,10 +1,11 @@
 use anyhow::{anyhow, Context, Result};
-use clap::Parser;
+use clap::{CommandFactory, FromArgMatches, Parser};
 use reqwest::Client;
-use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::{Path, PathBuf};
 use tokio::fs as async_fs;
+use video2en::audio::{AudioData, AudioEntry};
+use video2en::workspace::{check_output_folders, with_config_defaults, WorkspaceConfig, DEFAULT_TTS_URL};
 
 #[derive(Parser, Debug)]
 #[command(
@@ -13,7 +14,8 @@ use tokio::fs as async_fs;
     version,
     long_about = "A Rust CLI tool that reads English text files from a workspace directory and converts each line to audio using a TTS service. \
                   Supports batch processing of multiple text files. \
-                  Uses a workspace directory with fixed subdirectories: txt2audio_input/, txt2audio_output/"
+                  Uses a workspace directory with txt2audio_input/ and txt2audio_output/ subdirectories; \
+                  defaults can be set in <WORKSPACE>/video2en.toml"
 )]
 struct Args {
     /// Workspace directory containing txt2audio_input/ and txt2audio_output/ subdirectories
@@ -21,7 +23,7 @@ struct Args {
     workspace: PathBuf,
 
     /// TTS service URL
-    #[arg(long, value_name = "URL", default_value = "http://localhost:5000")]
+    #[arg(long, value_name = "URL", default_value = DEFAULT_TTS_URL)]
     tts_url: String,
 
     /// Male speaker audio file path for TTS
@@ -39,31 +41,51 @@ struct Args {
     /// Force overwrite existing files
     #[arg(long)]
     force: bool,
-}
 
-#[derive(Debug, Serialize, Deserialize)]
-struct AudioEntry {
-    text: String,
-    female_audio: String,
-    male_audio: String,
-    line_number: usize,
+    /// Rename the previous output directory to *_backup before processing instead of updating it in place
+    #[arg(long)]
+    archive_previous: bool,
+
+    /// Print the effective configuration (video2en.toml merged with command-line flags) and exit
+    #[arg(long)]
+    print_config: bool,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-struct AudioData {
-    entries: Vec<AudioEntry>,
-    total_count: usize,
-    output_directory: String,
-    input_file: String,
+impl Args {
+    /// 以 workspace 中 video2en.toml 的值作为默认值重新解析命令行，命令行参数优先
+    fn parse_with_config(config: &WorkspaceConfig) -> Self {
+        let tts = &config.tts;
+        let command = with_config_defaults(
+            Args::command(),
+            vec![
+                ("tts_url", tts.url.iter().cloned().collect()),
+                ("male_speaker_wav", tts.male_speaker_wav.iter().cloned().collect()),
+                ("female_speaker_wav", tts.female_speaker_wav.iter().cloned().collect()),
+                ("language", tts.language.iter().cloned().collect()),
+            ],
+        );
+        Args::from_arg_matches(&command.get_matches()).unwrap_or_else(|e| e.exit())
+    }
+
+    /// 合并命令行参数后实际生效的配置
+    fn effective_config(&self, config: &WorkspaceConfig) -> WorkspaceConfig {
+        let mut config = config.clone();
+        config.tts.url = Some(self.tts_url.clone());
+        config.tts.male_speaker_wav = Some(self.male_speaker_wav.clone());
+        config.tts.female_speaker_wav = Some(self.female_speaker_wav.clone());
+        config.tts.language = Some(self.language.clone());
+        config
+    }
 }
 
 struct Txt2Audio {
     args: Args,
+    config: WorkspaceConfig,
 }
 
 impl Txt2Audio {
-    fn new(args: Args) -> Self {
-        Self { args }
+    fn new(args: Args, config: WorkspaceConfig) -> Self {
+        Self { args, config }
     }
 
     fn get_workspace_paths(&self) -> Result<(PathBuf, PathBuf)> {
@@ -72,8 +94,8 @@ impl Txt2Audio {
             return Err(anyhow!("Workspace directory does not exist: {}", workspace.display()));
         }
         
-        let input_dir = workspace.join("txt2audio_input");
-        let output_dir = workspace.join("txt2audio_output");
+        let input_dir = workspace.join(&self.config.dirs.txt2audio_input);
+        let output_dir = workspace.join(&self.config.dirs.txt2audio_output);
 
         if !input_dir.exists() {
             return Err(anyhow!("Input directory does not exist: {}", input_dir.display()));
@@ -109,6 +131,8 @@ impl Txt2Audio {
             return Err(anyhow!("No .txt files found in input directory: {}", input_dir.display()));
         }
 
+        // 扩展名大小写不同的同名文件（a.txt、a.TXT）会写到同一个输出目录
+        check_output_folders(&text_files)?;
         Ok(text_files)
     }
 
@@ -347,8 +371,11 @@ impl Txt2Audio {
         let input_files = self.get_input_files()?;
         println!("📁 找到 {} 个输入文件", input_files.len());
         
+        // 默认在原输出目录中就地更新，已生成的音频直接复用
         let (_, output_dir) = self.get_workspace_paths()?;
-        self.handle_output_directory(&output_dir)?;
+        if self.args.archive_previous {
+            self.handle_output_directory(&output_dir)?;
+        }
 
         let mut total_processed = 0;
         let mut total_failed = 0;
@@ -357,7 +384,9 @@ impl Txt2Audio {
             println!("\n📄 处理文件 {}/{}: {}", 
                 index + 1, input_files.len(), input_file.display());
             
-            match self.process_text_file(input_file, &output_dir).await {
+            // 每个输入文件的音频和清单写在 <输出目录>/<输入文件名>/ 中
+            let file_output_dir = output_dir.join(input_file.file_stem().unwrap_or_default());
+            match self.process_text_file(input_file, &file_output_dir).await {
                 Ok(()) => {
                     total_processed += 1;
                     println!("✅ 文件 {} 处理完成!", 
@@ -386,6 +415,15 @@ impl Txt2Audio {
 async fn main() -> Result<()> {
     let args = Args::parse();
 
+    // workspace 中的 video2en.toml 提供参数默认值。第一次解析只用来确定 workspace，
+    // clap 只能在解析前设置默认值，所以读取配置后再解析一次
+    let config = WorkspaceConfig::load(&args.workspace)?;
+    let args = Args::parse_with_config(&config);
+    if args.print_config {
+        print!("{}", args.effective_config(&config).to_toml()?);
+        return Ok(());
+    }
+
     println!("🎵 TTS Text-to-Audio Converter");
     println!("📁 Workspace: {}", args.workspace.display());
     println!("🌐 TTS service: {}", args.tts_url);
@@ -393,6 +431,6 @@ async fn main() -> Result<()> {
     println!("🎙️ Female speaker: {}", args.female_speaker_wav);
     println!("🗣️ Language: {}", args.language);
 
-    let processor = Txt2Audio::new(args);
+    let processor = Txt2Audio::new(args, config);
     processor.run().await
 }
@@ -1,9 +1,23 @@
-use anyhow::{anyhow, Context, Result};
-use clap::Parser;
-use lingua::{Language, LanguageDetector, LanguageDetectorBuilder};
-use regex::Regex;
-use std::{fs, path::{Path, PathBuf}, process::Command};
-use video2en::youdao::YoudaoTranslator;
+use anyhow::{anyhow, Result};
+use clap::{error::ErrorKind, CommandFactory, FromArgMatches, Parser, Subcommand};
+use lingua::Language;
+use std::{path::PathBuf, time::Duration};
+use video2en::pipeline::{Artifact, Backend, BilingualLayout, OutputKind, Pipeline, PipelineConfig};
+use video2en::transcriber::TranscribeOptions;
+use video2en::cache::CacheMode;
+use video2en::throttle::{parse_rate, RetryPolicy};
+use video2en::translator::{Provider, TranslatorConfig};
+use video2en::anki::{AnkiAudio, AnkiOptions};
+use video2en::clip::{ClipFormat, ClipOptions};
+use video2en::dedup::{DedupMethod, DedupOptions, KeepStrategy};
+use video2en::doctor::{check_config, print_report, run_checks, Check, CheckStatus, DoctorOptions};
+use video2en::import::ExistingSubtitles;
+use video2en::language::{parse_language, LanguageOptions};
+use video2en::snapshot::{SnapshotFormat, SnapshotOptions};
+use video2en::subtitle::SubtitleFormat;
+use video2en::parallel::ParallelOptions;
+use video2en::vad::VadOptions;
+use video2en::workspace::{init_workspace, value_name, with_config_defaults, WorkspaceConfig, WorkspaceDirs, DEFAULT_TTS_URL};
 
 #[derive(Parser, Debug)]
 #[command(
@@ -13,13 +27,25 @@ use video2en::youdao::YoudaoTranslator;
     long_about = "A Rust CLI tool that extracts audio from video/audio files, \
                   transcribes them using Whisper, and filters for English content. \
                   Supports GPU acceleration (CUDA/OpenCL) for faster processing. \
-                  Outputs three files: full SRT, English-only SRT, and English-only TXT. \
-                  Uses a workspace directory with fixed subdirectories: input/, models/, output/"
+                  Outputs per input: full SRT, English-only SRT, English-only TXT and deduplicated English TXT. \
+                  Uses a workspace directory with input/, models/ and output/ subdirectories; \
+                  defaults can be set in <WORKSPACE>/video2en.toml"
 )]
 struct Args {
-    /// Workspace directory containing input/, models/, and output/ subdirectories
-    #[arg(short, long, value_name = "WORKSPACE_DIR")]
-    workspace: PathBuf,
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Workspace directory containing input/, models/, and output/ subdirectories (required)
+    #[arg(short, long, value_name = "WORKSPACE_DIR", global = true)]
+    workspace: Option<PathBuf>,
+
+    /// Transcription backend
+    #[arg(long, value_enum, default_value = "whisper-cli")]
+    backend: Backend,
+
+    /// whisper-cli executable name or path (used by the whisper-cli backend)
+    #[arg(long, value_name = "BIN", default_value = "whisper-cli")]
+    whisper_cli: String,
 
     /// Model filename (default: ggml-large.bin)
     #[arg(long, value_name = "MODEL_NAME")]
@@ -29,10 +55,30 @@ struct Args {
     #[arg(long, value_name = "auto|en|zh", default_value = "auto")]
     language: String,
 
-    /// Number of threads for recognition
+    /// Number of threads for recognition, shared by all --workers (default: all CPU cores)
     #[arg(long, value_name = "N")]
     threads: Option<usize>,
 
+    /// Beam size for beam search decoding (1 = greedy)
+    #[arg(long, value_name = "N", default_value = "8")]
+    beam_size: u32,
+
+    /// Number of candidates when sampling with non-zero temperature
+    #[arg(long, value_name = "N", default_value = "1")]
+    best_of: u32,
+
+    /// Sampling temperature
+    #[arg(long, value_name = "T", default_value = "0.0")]
+    temperature: f32,
+
+    /// Initial prompt to guide recognition (names, vocabulary, style)
+    #[arg(long, value_name = "TEXT")]
+    initial_prompt: Option<String>,
+
+    /// Let whisper translate speech to English (original non-English lines are lost)
+    #[arg(long)]
+    whisper_translate: bool,
+
     /// Use GPU acceleration (CUDA/Vulkan)
     #[arg(long)]
     gpu: bool,
@@ -41,737 +87,591 @@ struct Args {
     #[arg(long, value_name = "ID", default_value = "0")]
     gpu_device: u32,
 
-    /// Force overwrite existing output files
+    /// Regenerate artifacts even if they are up to date: without a value all output files,
+    /// or a comma-separated list (e.g. --force subtitles,anki or --force audio,transcript)
+    #[arg(long, value_enum, value_name = "ARTIFACTS", num_args = 0.., value_delimiter = ',')]
+    force: Option<Vec<Artifact>>,
+
+    /// Rename the previous output directory to *_backup before processing instead of updating it in place
     #[arg(long)]
-    force: bool,
+    archive_previous: bool,
+
+    /// Ignore completed stages in the state manifest and process every file from scratch
+    #[arg(long)]
+    restart: bool,
 
     /// Enable translation
     #[arg(long)]
     translate: bool,
 
-}
+    /// Translation provider
+    #[arg(long, value_enum, default_value = "youdao")]
+    translator: Provider,
 
-#[derive(Debug, Clone)]
-struct Segment {
-    start_ms: u32,
-    end_ms: u32,
-    text: String,
-    translation: Option<String>,
-}
+    /// Override the translation service base URL (e.g. a local stand-in server)
+    #[arg(long, value_name = "URL")]
+    translator_url: Option<String>,
 
+    /// API key for the translation service
+    #[arg(long, value_name = "KEY")]
+    translator_api_key: Option<String>,
 
-struct Video2En {
-    args: Args,
-    temp_dir: tempfile::TempDir,
-    language_detector: LanguageDetector,
-}
+    /// Offline dictionary file for the dict provider (english<TAB>translation per line)
+    #[arg(long, value_name = "FILE")]
+    dict_file: Option<PathBuf>,
 
-impl Video2En {
-    fn new(args: Args) -> Result<Self> {
-        let temp_dir = tempfile::tempdir().context("Failed to create temporary directory")?;
-        
-        let language_detector = LanguageDetectorBuilder::from_languages(&[
-            Language::English,
-            Language::Chinese,
-        ])
-        .build();
+    /// Target language for translation
+    #[arg(long, value_name = "LANG", default_value = "zh")]
+    target_lang: String,
 
-        Ok(Self {
-            args,
-            temp_dir,
-            language_detector,
-        })
-    }
+    /// How to use the translation cache in video2en_cache/
+    #[arg(long, value_enum, default_value = "read-write")]
+    cache_mode: CacheMode,
 
-    /// 获取workspace中的固定子文件夹路径
-    fn get_workspace_paths(&self) -> Result<(PathBuf, PathBuf, PathBuf)> {
-        let workspace = &self.args.workspace;
-        
-        // 确保workspace目录存在
-        if !workspace.exists() {
-            return Err(anyhow!("Workspace directory does not exist: {}", workspace.display()));
-        }
-        
-        let input_dir = workspace.join("video2en_input");
-        let models_dir = workspace.join("models");
-        let output_dir = workspace.join("video2en_output");
-        
-        // 检查input和models目录是否存在
-        if !input_dir.exists() {
-            return Err(anyhow!("Input directory does not exist: {}", input_dir.display()));
-        }
-        if !models_dir.exists() {
-            return Err(anyhow!("Models directory does not exist: {}", models_dir.display()));
-        }
-        
-        // 只创建output目录（如果不存在）
-        fs::create_dir_all(&output_dir).context("Failed to create output directory")?;
-        
-        Ok((input_dir, models_dir, output_dir))
-    }
+    /// Expire cached translations after N days
+    #[arg(long, value_name = "DAYS")]
+    cache_ttl_days: Option<u64>,
 
-    /// 获取输入文件路径列表（从workspace/input/目录中查找）
-    fn get_input_files(&self) -> Result<Vec<PathBuf>> {
-        let (input_dir, _, _) = self.get_workspace_paths()?;
-        
-        // 查找input目录中的视频/音频文件
-        let mut video_files = Vec::new();
-        for entry in fs::read_dir(&input_dir).context(format!("Failed to read input directory: {}", input_dir.display()))? {
-            let entry = entry.context("Failed to read directory entry")?;
-            let path = entry.path();
-            
-            if path.is_file() {
-                if let Some(extension) = path.extension() {
-                    let ext = extension.to_string_lossy().to_lowercase();
-                    if matches!(ext.as_str(), "mp4" | "avi" | "mkv" | "mov" | "wmv" | "flv" | "webm" | 
-                               "mp3" | "wav" | "flac" | "aac" | "ogg" | "m4a") {
-                        video_files.push(path);
-                    }
-                }
-            }
-        }
-        
-        if video_files.is_empty() {
-            return Err(anyhow!("No video/audio files found in input directory: {}", input_dir.display()));
-        }
-        
-        Ok(video_files)
-    }
-
-    /// 获取模型文件路径（从workspace/models/目录中查找）
-    fn get_model_file(&self, model_name: Option<String>) -> Result<PathBuf> {
-        let (_, models_dir, _) = self.get_workspace_paths()?;
-        
-        // 确定要查找的模型文件名
-        let target_model_name = model_name.unwrap_or_else(|| "ggml-large.bin".to_string());
-        let target_path = models_dir.join(&target_model_name);
-        
-        // 检查指定的模型文件是否存在
-        if target_path.exists() && target_path.is_file() {
-            return Ok(target_path);
-        }
-        
-        // 如果指定的文件不存在，查找models目录中的所有.bin文件
-        let mut model_files = Vec::new();
-        for entry in fs::read_dir(&models_dir).context(format!("Failed to read models directory: {}", models_dir.display()))? {
-            let entry = entry.context("Failed to read directory entry")?;
-            let path = entry.path();
-            
-            if path.is_file() {
-                if let Some(extension) = path.extension() {
-                    if extension == "bin" {
-                        model_files.push(path);
-                    }
-                }
-            }
-        }
-        
-        if model_files.is_empty() {
-            return Err(anyhow!("No .bin model files found in models directory: {}", models_dir.display()));
-        }
-        
-        if model_files.len() > 1 {
-            return Err(anyhow!("Multiple model files found in models directory. Please specify model name with --model-name or keep only one file: {:?}", model_files));
-        }
-        
-        Ok(model_files[0].clone())
-    }
+    /// Maximum number of concurrent translation requests
+    #[arg(long, value_name = "N", default_value = "4")]
+    translate_concurrency: usize,
 
-    async fn run(&self) -> Result<()> {
-        // Check ffmpeg availability
-        self.check_ffmpeg()?;
+    /// Maximum translation requests per second
+    #[arg(long, value_name = "RPS", value_parser = parse_rate, default_value = "5")]
+    translate_rate: f64,
 
-        // 获取所有输入文件
-        let input_files = self.get_input_files()?;
-        
-        println!("📁 找到 {} 个输入文件", input_files.len());
+    /// Retries for network, 5xx and JSON errors (exponential backoff)
+    #[arg(long, value_name = "N", default_value = "3")]
+    translate_retries: u32,
 
-        let (_, _, output_dir) = self.get_workspace_paths()?;
-        
-        // 检查并处理输出目录
-        self.handle_output_directory(&output_dir)?;
-        
-        // 循环处理每个输入文件
-        for (index, input_file) in input_files.iter().enumerate() {
-            println!("\n🎬 处理文件 {}/{}: {}", index + 1, input_files.len(), input_file.display());
-            
-            // 获取当前文件的输出前缀
-            // let output_prefix = self.get_output_prefix_for_file(input_file)?;
-            
-            // Extract audio
-            let audio_path = self.extract_audio_for_file(input_file, &output_dir)?;
-            
-            // Transcribe with whisper-cli.exe
-            let segments = self.transcribe(&audio_path, &output_dir)?;
-            
-            // 分析和统计英文内容
-            self.write_outputs(&segments, &audio_path).await?;
-            
-            println!("✅ 文件 {} 处理完成!", input_file.file_name().unwrap_or_default().to_string_lossy());
-            println!("📁 生成的文件:");
-            println!("   - {} (音频文件)", audio_path.display());
-        }
-
-        println!("\n🎉 所有文件处理完成！共处理了 {} 个文件", input_files.len());
-        Ok(())
-    }
-
-    fn check_ffmpeg(&self) -> Result<()> {
-        which::which("ffmpeg").map_err(|_| {
-            anyhow!(
-                "ffmpeg not found in PATH. Please install ffmpeg:\n\
-                 Windows: Download from https://ffmpeg.org/download.html\n\
-                 Or use chocolatey: choco install ffmpeg\n\
-                 Or use winget: winget install ffmpeg"
-            )
-        })?;
-        Ok(())
-    }
-
-    fn handle_output_directory(&self, output_dir: &Path) -> Result<()> {
-        if !output_dir.exists() {
-            // 输出目录不存在，创建它
-            fs::create_dir_all(output_dir).context("Failed to create output directory")?;
-            println!("📁 创建输出目录: {}", output_dir.display());
-            return Ok(());
-        }
-
-        // 检查输出目录是否为空
-        let mut entries = fs::read_dir(output_dir).context("Failed to read output directory")?;
-        if entries.next().is_none() {
-            // 目录为空，直接使用
-            println!("📁 输出目录为空，直接使用: {}", output_dir.display());
-            return Ok(());
-        }
+    /// Only retry translations that failed in a previous run, then exit
+    #[arg(long)]
+    retry_failed_translations: bool,
+
+    /// Output files to write for each input (comma separated)
+    #[arg(
+        long,
+        value_enum,
+        value_delimiter = ',',
+        default_value = "all-subs,en-subs,en-txt,unique-txt,bilingual-txt,bilingual-subs,bilingual-tsv,json,jsonl"
+    )]
+    outputs: Vec<OutputKind>,
+
+    /// Transcribe overlapping chunks of the audio in parallel with this many workers (1 = whole file at once)
+    #[arg(long, value_name = "N", default_value_t = 1)]
+    workers: usize,
+
+    /// Length of each parallel chunk, in seconds (at most one day)
+    #[arg(long, value_name = "SECS", default_value_t = 300, value_parser = clap::value_parser!(u32).range(1..=86_400))]
+    chunk_secs: u32,
+
+    /// Overlap between neighbouring parallel chunks, in seconds (less than --chunk-secs, at most one hour)
+    #[arg(long, value_name = "SECS", default_value_t = 5, value_parser = clap::value_parser!(u32).range(0..=3_600))]
+    chunk_overlap_secs: u32,
+
+    /// Detect speech before transcribing and only transcribe speech chunks (skips long silences)
+    #[arg(long)]
+    vad: bool,
 
-        // 目录不为空，需要重命名
-        println!("📁 输出目录不为空，正在重命名: {}", output_dir.display());
-        
-        let mut backup_name = output_dir.with_file_name(format!("{}_backup", output_dir.file_name().unwrap_or_default().to_string_lossy()));
-        let mut counter = 1;
-        
-        // 处理多次重命名的情况
-        while backup_name.exists() {
-            backup_name = output_dir.with_file_name(format!("{}_backup_{}", 
-                output_dir.file_name().unwrap_or_default().to_string_lossy(), 
-                counter
-            ));
-            counter += 1;
-        }
-        
-        // 重命名原目录
-        fs::rename(output_dir, &backup_name).context("Failed to rename output directory")?;
-        println!("📁 已重命名为: {}", backup_name.display());
-        
-        // 创建新的输出目录
-        fs::create_dir_all(output_dir).context("Failed to create new output directory")?;
-        println!("📁 创建新的输出目录: {}", output_dir.display());
-        
-        Ok(())
-    }
+    /// Frame energy (dBFS) at or above which audio counts as speech
+    #[arg(long, value_name = "DB", default_value_t = -40.0, allow_hyphen_values = true)]
+    vad_threshold_db: f32,
 
+    /// Pauses shorter than this keep speech in the same chunk
+    #[arg(long, value_name = "MS", default_value_t = 700)]
+    vad_min_silence_ms: u32,
 
-    fn extract_audio(&self, output_prefix: &Path) -> Result<PathBuf> {
-        let input_files = self.get_input_files()?;
-        let input_path = &input_files[0]; // 使用第一个文件
-        
-        // 获取输入文件名（不含扩展名）
-        let input_stem = input_path
-            .file_stem()
-            .ok_or_else(|| anyhow!("Invalid input filename"))?
-            .to_string_lossy()
-            .to_string();
-        
-        // 创建音频文件路径：输出目录 + 输入文件名 + .wav
-        let audio_path = output_prefix.join(format!("{}.wav", input_stem));
+    /// Speech shorter than this is treated as noise
+    #[arg(long, value_name = "MS", default_value_t = 250)]
+    vad_min_speech_ms: u32,
 
-        // 确保音频文件的父目录存在
-        if let Some(parent) = audio_path.parent() {
-            fs::create_dir_all(parent).context("Failed to create audio output directory")?;
-        }
+    /// Audio kept before and after each speech chunk
+    #[arg(long, value_name = "MS", default_value_t = 200)]
+    vad_padding_ms: u32,
 
-        println!("🎵 Extracting audio from: {}", input_path.display());
-        println!("💾 Audio will be saved to: {}", audio_path.display());
-
-        let status = Command::new("ffmpeg")
-            .args([
-                "-y",                    // Overwrite output
-                "-i", input_path.to_str().unwrap(),
-                "-vn",                   // No video
-                "-ac", "1",             // Mono
-                "-ar", "16000",         // 16kHz sample rate
-                "-f", "wav",            // WAV format
-                audio_path.to_str().unwrap(),
-            ])
-            .status()
-            .context("Failed to execute ffmpeg")?;
-
-        if !status.success() {
-            return Err(anyhow!("ffmpeg failed with exit code: {}", status));
-        }
+    /// Longest speech chunk handed to whisper, in seconds (at most one day)
+    #[arg(long, value_name = "SECS", default_value_t = 600, value_parser = clap::value_parser!(u32).range(1..=86_400))]
+    vad_max_chunk_secs: u32,
 
-        Ok(audio_path)
-    }
+    /// Language of the segments to keep (ISO 639-1 code or English name, e.g. en, ja, es)
+    #[arg(long, value_name = "LANG", value_parser = parse_language, default_value = "en")]
+    filter_language: Language,
 
-    fn extract_audio_for_file(&self, input_path: &Path, output_prefix: &Path) -> Result<PathBuf> {
-        // 获取输入文件名（不含扩展名）
-        let input_stem = input_path
-            .file_stem()
-            .ok_or_else(|| anyhow!("Invalid input filename"))?
-            .to_string_lossy()
-            .to_string();
-        
-        // 创建音频文件路径：输出目录 + 输入文件名 + .wav
-        let audio_path = output_prefix.join(format!("{}.wav", input_stem));
+    /// Languages the detector chooses between (comma separated; the filter language is always included)
+    #[arg(long, value_name = "LANGS", value_parser = parse_language, value_delimiter = ',', default_value = "en,zh")]
+    candidate_languages: Vec<Language>,
 
-        // 确保音频文件的父目录存在
-        if let Some(parent) = audio_path.parent() {
-            fs::create_dir_all(parent).context("Failed to create audio output directory")?;
-        }
+    /// Share (0-1) of characters in the filter language's script that marks a segment as kept without detection
+    #[arg(long, default_value_t = 0.6)]
+    script_ratio: f64,
 
-        println!("🎵 Extracting audio from: {}", input_path.display());
-        println!("💾 Audio will be saved to: {}", audio_path.display());
-
-        let status = Command::new("ffmpeg")
-            .args([
-                "-y",                    // Overwrite output
-                "-i", input_path.to_str().unwrap(),
-                "-vn",                   // No video
-                "-ac", "1",             // Mono
-                "-ar", "16000",         // 16kHz sample rate
-                "-f", "wav",            // WAV format
-                audio_path.to_str().unwrap(),
-            ])
-            .status()
-            .context("Failed to execute ffmpeg")?;
-
-        if !status.success() {
-            return Err(anyhow!("ffmpeg failed with exit code: {}", status));
-        }
+    /// Minimum detector confidence (0-1) for a segment to count as the filter language
+    #[arg(long, default_value_t = 0.0)]
+    min_confidence: f64,
 
-        Ok(audio_path)
-    }
+    /// Split lines mixing the filter language with other languages into separate segments
+    #[arg(long)]
+    split_mixed: bool,
 
+    /// Skip sentences already seen in other files or previous runs (workspace-wide sentence index)
+    #[arg(long)]
+    global_dedup: bool,
 
+    /// How near-duplicate English sentences are detected
+    #[arg(long, value_enum, default_value = "exact")]
+    dedup: DedupMethod,
 
-    fn transcribe(&self, audio_path: &Path, output_prefix: &Path) -> Result<Vec<Segment>> {
-        println!("🤖 Transcribing audio using whisper-cli.exe...");
-        
-        // 检查 whisper-cli.exe 是否可用
-        self.check_whisper_cli()?;
-        
-        // 获取输出目录和文件名
-        // let output_prefix = self.get_output_prefix()?;
-        // let output_dir = &output_prefix;
-        let output_name = audio_path.file_stem().unwrap_or_default().to_string_lossy().to_string();
-        let txt_output = output_prefix.join(format!("{}_raw", output_name));
+    /// Similarity (0-1) at or above which two sentences count as duplicates (edit-distance/jaccard)
+    #[arg(long, default_value_t = 0.8)]
+    dedup_threshold: f64,
 
-        // println!("📁 Output directory: {}", output_dir.display());
-        
-        // 构建 whisper-cli 命令 - 使用指定的参数格式
-        let model = self.get_model_file(self.args.model_name.clone())?;
-        let mut cmd = Command::new("whisper-cli.exe");
-        cmd.arg("-m").arg(model)
-           .arg("-f").arg(audio_path.to_str().unwrap())
-        //    .arg("-l").arg("en")  // 固定为英文
-           .arg("-tr")           // 翻译
-           .arg("-bs").arg("8")  // batch size
-           .arg("-bo").arg("1")  // best of
-           .arg("-t").arg("8")   // threads
-           .arg("-otxt")         // 输出文本格式
-           .arg("-of").arg(txt_output.clone());
-        
-        println!("🎯 Running whisper-cli with command: {:?}", cmd);
-        
-        // 执行命令
-        let output = cmd.output()
-            .context("Failed to execute whisper-cli.exe")?;
-        
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            return Err(anyhow!("whisper-cli failed:\nSTDERR: {}\nSTDOUT: {}", stderr, stdout));
-        }
-        
-        // 读取生成的文本文件（whisper-cli会自动添加.txt扩展名）
-        let txt_output = output_prefix.join(format!("{}_raw.txt", output_name));
-        let text_content = fs::read_to_string(&txt_output)
-            .context(format!("Failed to read generated text file: {}", txt_output.display()))?;
-        
-        // 解析文本内容为segments（每行作为一个segment）
-        let segments = self.parse_text_to_segments(&text_content);
-        
-        // 保留whisper-cli生成的中间文本文件
-        println!("📄 保留中间文本文件: {}", txt_output.display());
-        
-        println!("✅ Transcribed {} text segments", segments.len());
-        Ok(segments)
-    }
-    
-    fn check_whisper_cli(&self) -> Result<()> {
-        which::which("whisper-cli.exe").map_err(|_| {
-            anyhow!(
-                "whisper-cli.exe not found in PATH. Please install whisper-cli:\n\
-                 Download from: https://github.com/ggerganov/whisper.cpp/releases\n\
-                 Or build from source: https://github.com/ggerganov/whisper.cpp"
-            )
-        })?;
-        Ok(())
-    }
-    
-    fn parse_text_to_segments(&self, text_content: &str) -> Vec<Segment> {
-        let mut segments = Vec::new();
-        let mut start_time = 0u32;
-        
-        for line in text_content.lines() {
-            let text = line.trim();
-            if !text.is_empty() {
-                // 简单的时间分配：每行假设持续3秒
-                let end_time = start_time + 3000;
-                
-                segments.push(Segment {
-                    start_ms: start_time,
-                    end_ms: end_time,
-                    text: text.to_string(),
-                    translation: None,
-                });
-                
-                start_time = end_time;
-            }
-        }
-        
-        segments
-    }
-    
-    fn parse_srt(&self, srt_content: &str) -> Result<Vec<Segment>> {
-        let mut segments = Vec::new();
-        let re = Regex::new(r"(\d+)\n(\d{2}:\d{2}:\d{2},\d{3}) --> (\d{2}:\d{2}:\d{2},\d{3})\n(.*?)(?=\n\d+\n|\n*$)").unwrap();
-        
-        for cap in re.captures_iter(srt_content) {
-            let start_time = self.parse_timestamp(&cap[2])?;
-            let end_time = self.parse_timestamp(&cap[3])?;
-            let text = cap[4].trim().to_string();
-            
-            if !text.is_empty() {
-                segments.push(Segment {
-                    start_ms: start_time,
-                    end_ms: end_time,
-                    text,
-                    translation: None,
-                });
-            }
-        }
-        
-        Ok(segments)
-    }
-    
-    fn parse_timestamp(&self, timestamp: &str) -> Result<u32> {
-        let parts: Vec<&str> = timestamp.split(':').collect();
-        if parts.len() != 3 {
-            return Err(anyhow!("Invalid timestamp format: {}", timestamp));
-        }
-        
-        let hours: u32 = parts[0].parse()?;
-        let minutes: u32 = parts[1].parse()?;
-        let seconds_parts: Vec<&str> = parts[2].split(',').collect();
-        if seconds_parts.len() != 2 {
-            return Err(anyhow!("Invalid seconds format: {}", parts[2]));
-        }
-        
-        let seconds: u32 = seconds_parts[0].parse()?;
-        let milliseconds: u32 = seconds_parts[1].parse()?;
-        
-        Ok(hours * 3600000 + minutes * 60000 + seconds * 1000 + milliseconds)
-    }
-    
+    /// Expand contractions ("I'm" -> "I am") before comparing sentences
+    #[arg(long)]
+    expand_contractions: bool,
 
-    // fn get_output_prefix(&self) -> Result<PathBuf> {
-    //     let (_, _, output_dir) = self.get_workspace_paths()?;
-        
-    //     // 获取输入文件名作为输出前缀
-    //     let input_files = self.get_input_files()?;
-    //     let input_file = &input_files[0]; // 使用第一个文件
-    //     let input_stem = input_file
-    //         .file_stem()
-    //         .ok_or_else(|| anyhow!("Invalid input filename"))?
-    //         .to_string_lossy()
-    //         .to_string();
-        
-    //     let output_prefix = output_dir.join(input_stem);
-        
-    //     // 确保输出目录存在
-    //     if let Some(parent) = output_prefix.parent() {
-    //         fs::create_dir_all(parent).context("Failed to create output directory")?;
-    //     }
-        
-    //     Ok(output_prefix)
-    // }
+    /// Which sentence of a duplicate group to keep
+    #[arg(long, value_enum, default_value = "earliest")]
+    dedup_keep: KeepStrategy,
 
+    /// Cut an audio clip for every deduplicated English segment and write a manifest
+    #[arg(long)]
+    clips: bool,
 
-    async fn write_outputs(&self, segments: &[Segment], audio_path: &Path) -> Result<()> {
-        // 过滤英文segments
-        let english_segments: Vec<&Segment> = segments
-            .iter()
-            .filter(|segment| self.is_english(&segment.text))
-            .collect();
+    /// Silence kept before and after each clip in milliseconds
+    #[arg(long, default_value_t = 200)]
+    clip_padding_ms: u32,
 
-        // 去重：使用HashSet来存储唯一的英文文本
-        use std::collections::HashSet;
-        let mut unique_english_texts = HashSet::new();
-        let mut deduplicated_segments = Vec::new();
-        
-        for segment in &english_segments {
-            let normalized_text = self.normalize_text(&segment.text);
-            if unique_english_texts.insert(normalized_text.clone()) {
-                deduplicated_segments.push((*segment).clone());
-            }
-        }
+    /// Fade-in/fade-out length of each clip in milliseconds
+    #[arg(long, default_value_t = 30)]
+    clip_fade_ms: u32,
 
-        // 统计信息
-        let total_segments = segments.len();
-        let english_segments_count = english_segments.len();
-        let unique_english_count = deduplicated_segments.len();
-        let duplicate_count = english_segments_count - unique_english_count;
-        let non_english_segments_count = total_segments - english_segments_count;
-
-        println!("📊 统计结果:");
-        println!("   - 总段落数: {}", total_segments);
-        println!("   - 英文段落数: {}", english_segments_count);
-        println!("   - 去重后英文段落数: {}", unique_english_count);
-        println!("   - 重复英文段落数: {}", duplicate_count);
-        println!("   - 非英文段落数: {}", non_english_segments_count);
-        
-        if total_segments > 0 {
-            let english_percentage = (english_segments_count as f64 / total_segments as f64) * 100.0;
-            let unique_percentage = (unique_english_count as f64 / total_segments as f64) * 100.0;
-            println!("   - 英文比例: {:.1}%", english_percentage);
-            println!("   - 去重后英文比例: {:.1}%", unique_percentage);
-        }
+    /// Encoding of the audio clips (mp3/ogg are encoded with ffmpeg)
+    #[arg(long, value_enum, default_value = "wav")]
+    clip_format: ClipFormat,
 
-        // 保存去重后的英文内容到文件
-        if !deduplicated_segments.is_empty() {
-            // 如果启用了翻译功能，则翻译去重后的英文内容
-            if self.args.translate {
-                self.translate_segments(&mut deduplicated_segments).await?;
-            }
-            
-            let output_file = audio_path.with_file_name(
-                audio_path.file_stem()
-                    .unwrap_or_default()
-                    .to_string_lossy()
-                    .to_string() + ".txt"
-            );
-            self.save_unique_english(&deduplicated_segments.iter().collect::<Vec<_>>(), &output_file)?;
-            
-            // 显示去重后的英文内容预览
-            println!("📝 去重后英文内容预览 (前10段):");
-            for (i, segment) in deduplicated_segments.iter().take(10).enumerate() {
-                println!("   {}. {}", i + 1, segment.text);
-                if let Some(ref translation) = segment.translation {
-                    println!("      中文: {}", translation);
-                }
-            }
-            if deduplicated_segments.len() > 10 {
-                println!("   ... 还有 {} 段去重后的英文内容", deduplicated_segments.len() - 10);
-            }
-            
-            println!("💾 去重后的英文内容已保存到: {}", output_file.display());
-        }
+    /// Grab a still frame at the midpoint of every deduplicated English segment
+    #[arg(long)]
+    snapshots: bool,
 
-        Ok(())
-    }
+    /// Snapshot width in pixels (0 keeps the source resolution)
+    #[arg(long, default_value_t = 640)]
+    snapshot_width: u32,
 
-    fn write_srt(&self, segments: &[&Segment], output_path: &Path, description: &str) -> Result<()> {
-        if output_path.exists() && !self.args.force {
-            println!("[skip] {} SRT already exists: {}", description, output_path.display());
-            return Ok(());
-        }
+    /// Snapshot height in pixels (scaled proportionally when omitted)
+    #[arg(long)]
+    snapshot_height: Option<u32>,
 
-        println!("📝 Writing {} SRT: {}", description, output_path.display());
-        
-        let mut content = String::new();
-        for (i, segment) in segments.iter().enumerate() {
-            let start_time = self.format_timestamp(segment.start_ms);
-            let end_time = self.format_timestamp(segment.end_ms);
-            
-            content.push_str(&format!("{}\n", i + 1));
-            content.push_str(&format!("{} --> {}\n", start_time, end_time));
-            content.push_str(&format!("{}\n\n", segment.text));
-        }
+    /// Snapshot image format
+    #[arg(long, value_enum, default_value = "jpg")]
+    snapshot_format: SnapshotFormat,
 
-        fs::write(output_path, content)
-            .context(format!("Failed to write SRT file: {}", output_path.display()))?;
+    /// Export the deduplicated sentences as an Anki deck (<name>.apkg)
+    #[arg(long)]
+    anki: bool,
 
-        Ok(())
-    }
+    /// Parent deck name; each input file becomes a sub-deck
+    #[arg(long, default_value = "video2en")]
+    anki_deck: String,
 
-    fn write_txt(&self, segments: &[&Segment], output_path: &Path) -> Result<()> {
-        if output_path.exists() && !self.args.force {
-            println!("[skip] English TXT already exists: {}", output_path.display());
-            return Ok(());
-        }
+    /// Audio attached to each Anki note
+    #[arg(long, value_enum, default_value = "clip")]
+    anki_audio: AnkiAudio,
 
-        println!("📄 Writing English TXT: {}", output_path.display());
-        
-        let content: String = segments
-            .iter()
-            .map(|segment| segment.text.clone())
-            .collect::<Vec<_>>()
-            .join("\n\n");
+    /// txt2audio <name>_audio_data.json used when --anki-audio=tts
+    #[arg(long)]
+    anki_tts_data: Option<PathBuf>,
 
-        fs::write(output_path, content)
-            .context(format!("Failed to write TXT file: {}", output_path.display()))?;
+    /// Use sidecar (.srt/.vtt/.ass) or embedded text subtitles instead of transcribing when available
+    #[arg(long, value_enum, default_value = "prefer")]
+    existing_subtitles: ExistingSubtitles,
 
-        Ok(())
-    }
+    /// Subtitle formats written for every subtitle output (comma separated)
+    #[arg(long, value_enum, value_delimiter = ',', default_value = "srt,vtt")]
+    subtitle_formats: Vec<SubtitleFormat>,
 
-    fn is_english(&self, text: &str) -> bool {
-        // Clean text for analysis
-        let cleaned = self.clean_text(text);
-        
-        if cleaned.is_empty() {
-            return false;
-        }
+    /// Reuse <name>.json/.jsonl transcripts in the output directory instead of running whisper again
+    #[arg(long)]
+    from_transcripts: bool,
 
-        // Method 1: ASCII letter ratio check
-        let ascii_letters: usize = cleaned
-            .chars()
-            .filter(|c| c.is_ascii_alphabetic())
-            .count();
-        
-        let total_chars = cleaned.chars().count();
-        if total_chars > 0 {
-            let ascii_ratio = ascii_letters as f64 / total_chars as f64;
-            if ascii_ratio >= 0.6 {
-                return true;
-            }
-        }
+    /// Layout of bilingual outputs
+    #[arg(long, value_enum, default_value = "en-over-zh")]
+    bilingual_layout: BilingualLayout,
 
-        // Method 2: Language detection fallback
-        if let Some(language) = self.language_detector.detect_language_of(&cleaned) {
-            return language == Language::English;
-        }
+    /// Print the effective configuration (video2en.toml merged with command-line flags) and exit
+    #[arg(long)]
+    print_config: bool,
+}
 
-        false
-    }
+#[derive(Subcommand, Debug, Clone, Copy, PartialEq, Eq)]
+enum Command {
+    /// Create the workspace directories and a sample video2en.toml
+    Init,
+    /// Check ffmpeg, the transcription backend, the model file and the translation and TTS services
+    Doctor,
+}
 
-    fn clean_text(&self, text: &str) -> String {
-        // Remove common non-text symbols and normalize
-        let re = Regex::new(r"[^\p{L}\p{N}\s]").unwrap();
-        re.replace_all(text, " ")
-            .split_whitespace()
-            .collect::<Vec<_>>()
-            .join(" ")
+impl Args {
+    /// 以 workspace 中 video2en.toml 的值作为默认值重新解析命令行，命令行参数优先
+    fn parse_with_config(config: &WorkspaceConfig) -> Self {
+        Self::try_parse_with_config(config).unwrap_or_else(|e| e.exit())
     }
 
-    fn normalize_text(&self, text: &str) -> String {
-        // 标准化文本用于去重比较
-        text.trim()
-            .to_lowercase()
-            .chars()
-            .filter(|c| c.is_alphabetic() || c.is_whitespace())
-            .collect::<String>()
-            .split_whitespace()
-            .collect::<Vec<_>>()
-            .join(" ")
+    /// 同 `parse_with_config`，配置文件中的值或命令行参数无效时返回错误
+    fn try_parse_with_config(config: &WorkspaceConfig) -> Result<Self, clap::Error> {
+        let video2en = &config.video2en;
+        let translation = &config.translation;
+        let command = with_config_defaults(
+            Args::command(),
+            vec![
+                ("backend", video2en.backend.iter().cloned().collect()),
+                ("whisper_cli", video2en.whisper_cli.iter().cloned().collect()),
+                ("model_name", video2en.model.iter().cloned().collect()),
+                ("language", video2en.language.iter().cloned().collect()),
+                ("filter_language", video2en.filter_language.iter().cloned().collect()),
+                ("candidate_languages", video2en.candidate_languages.clone().unwrap_or_default()),
+                ("outputs", video2en.outputs.clone().unwrap_or_default()),
+                ("subtitle_formats", video2en.subtitle_formats.clone().unwrap_or_default()),
+                ("bilingual_layout", video2en.bilingual_layout.iter().cloned().collect()),
+                ("translator", translation.provider.iter().cloned().collect()),
+                ("translator_url", translation.url.iter().cloned().collect()),
+                ("target_lang", translation.target_lang.iter().cloned().collect()),
+                (
+                    "dict_file",
+                    translation.dict_file.iter().map(|path| path.to_string_lossy().to_string()).collect(),
+                ),
+            ],
+        );
+        let args = Args::from_arg_matches(&command.try_get_matches()?)?;
+        args.check_chunk_overlap()?;
+        Ok(args)
     }
 
-    async fn translate_segments(&self, segments: &mut Vec<Segment>) -> Result<()> {
-        let translator = YoudaoTranslator;
-        
-        println!("🌐 正在翻译英文内容...");
-        
-        let total_count = segments.len();
-        for (i, segment) in segments.iter_mut().enumerate() {
-            print!("\r🔄 翻译进度: {}/{}", i + 1, total_count);
-            std::io::Write::flush(&mut std::io::stdout()).ok();
-            
-            match translator.translate(&segment.text).await {
-                Ok(word_info) => {
-                    if let Some(fanyi) = &word_info.fanyi {
-                        segment.translation = Some(fanyi.tran.clone());
-                    } else {
-                        segment.translation = Some("未找到翻译".to_string());
-                    }
-                }
-                Err(e) => {
-                    println!("\n⚠️ 翻译失败: {} - {}", segment.text, e);
-                    segment.translation = Some("翻译失败".to_string());
-                }
-            }
-            
-            // 添加小延迟避免API限制
-            tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+    /// 相邻块的重叠必须短于块长，否则分块无法向前推进
+    fn check_chunk_overlap(&self) -> Result<(), clap::Error> {
+        if self.chunk_overlap_secs >= self.chunk_secs {
+            return Err(Args::command().error(
+                ErrorKind::ArgumentConflict,
+                format!(
+                    "--chunk-overlap-secs ({}) must be less than --chunk-secs ({})",
+                    self.chunk_overlap_secs, self.chunk_secs
+                ),
+            ));
         }
-        
-        println!("\n✅ 翻译完成!");
         Ok(())
     }
 
+    /// 合并命令行参数后实际生效的配置
+    fn effective_config(&self, config: &WorkspaceConfig) -> WorkspaceConfig {
+        let mut config = config.clone();
+        let video2en = &mut config.video2en;
+        video2en.backend = Some(value_name(&self.backend));
+        video2en.whisper_cli = Some(self.whisper_cli.clone());
+        video2en.model = Some(self.model_name.clone().unwrap_or_else(|| "ggml-large.bin".to_string()));
+        video2en.language = Some(self.language.clone());
+        video2en.filter_language = Some(self.filter_language.iso_code_639_1().to_string());
+        video2en.candidate_languages = Some(
+            self.candidate_languages
+                .iter()
+                .map(|language| language.iso_code_639_1().to_string())
+                .collect(),
+        );
+        video2en.outputs = Some(self.outputs.iter().map(value_name).collect());
+        video2en.subtitle_formats = Some(self.subtitle_formats.iter().map(value_name).collect());
+        video2en.bilingual_layout = Some(value_name(&self.bilingual_layout));
+
+        let translation = &mut config.translation;
+        translation.provider = Some(value_name(&self.translator));
+        translation.url = self.translator_url.clone();
+        translation.target_lang = Some(self.target_lang.clone());
+        translation.dict_file = self.dict_file.clone();
+        config
+    }
 
-    fn save_unique_english(&self, segments: &Vec<&Segment>, output_path: &Path) -> Result<()> {
-        if output_path.exists() && !self.args.force {
-            println!("[skip] 去重英文文件已存在: {}", output_path.display());
-            return Ok(());
+    /// doctor 检查的对象
+    fn doctor_options(&self, config: &WorkspaceConfig) -> DoctorOptions {
+        DoctorOptions {
+            workspace: self.workspace.clone().unwrap_or_default(),
+            dirs: config.dirs.clone(),
+            backend: self.backend,
+            whisper_cli: self.whisper_cli.clone(),
+            model_name: self.model_name.clone().unwrap_or_else(|| "ggml-large.bin".to_string()),
+            translator: self.translator_config(),
+            tts_url: config.tts.url.clone().unwrap_or_else(|| DEFAULT_TTS_URL.to_string()),
         }
+    }
 
-        println!("📄 保存去重后的英文内容到: {}", output_path.display());
-        
-        let mut content = String::new();
-        // content.push_str("# 去重后的英文内容 (中英文对照)\n");
-        // content.push_str(&format!("# 总计 {} 段唯一英文内容\n\n", segments.len()));
-        
-        for (_i, segment) in segments.iter().enumerate() {
-            content.push_str(&format!("{}\n", segment.text));
-            // if let Some(ref translation) = segment.translation {
-            //     content.push_str(&format!("   中文: {}\n", translation));
-            // }
-            // content.push_str("\n");
+    fn translator_config(&self) -> TranslatorConfig {
+        TranslatorConfig {
+            provider: self.translator,
+            base_url: self.translator_url.clone(),
+            api_key: self.translator_api_key.clone(),
+            dict_file: self.dict_file.clone(),
+            source_lang: self.filter_language.iso_code_639_1().to_string(),
+            target_lang: self.target_lang.clone(),
+            cache_mode: self.cache_mode,
+            cache_ttl: self.cache_ttl_days.map(|days| Duration::from_secs(days * 24 * 3600)),
+            concurrency: self.translate_concurrency,
+            rate_limit: self.translate_rate,
+            retry: RetryPolicy {
+                max_retries: self.translate_retries,
+                ..Default::default()
+            },
         }
-
-        fs::write(output_path, content)
-            .context(format!("Failed to write unique English file: {}", output_path.display()))?;
-
-        Ok(())
     }
 
-    fn format_timestamp(&self, ms: u32) -> String {
-        let seconds = ms / 1000;
-        let milliseconds = ms % 1000;
-        let hours = seconds / 3600;
-        let minutes = (seconds % 3600) / 60;
-        let secs = seconds % 60;
-        
-        format!("{:02}:{:02}:{:02},{:03}", hours, minutes, secs, milliseconds)
+    fn into_config(self, dirs: WorkspaceDirs) -> PipelineConfig {
+        let translator = self.translator_config();
+        let defaults = TranscribeOptions::default();
+        let transcribe = TranscribeOptions {
+            language: self.language,
+            threads: self.threads.unwrap_or(defaults.threads),
+            use_gpu: self.gpu,
+            gpu_device: self.gpu_device,
+            beam_size: self.beam_size,
+            best_of: self.best_of,
+            temperature: self.temperature,
+            initial_prompt: self.initial_prompt,
+            translate: self.whisper_translate,
+        };
+
+        let snapshots = self.snapshots.then_some(SnapshotOptions {
+            width: Some(self.snapshot_width).filter(|width| *width > 0),
+            height: self.snapshot_height,
+            format: self.snapshot_format,
+        });
+
+        let anki = self.anki.then_some(AnkiOptions {
+            deck: self.anki_deck,
+            audio: self.anki_audio,
+            tts_data: self.anki_tts_data,
+        });
+
+        let clips = self.clips.then_some(ClipOptions {
+            padding_ms: self.clip_padding_ms,
+            fade_ms: self.clip_fade_ms,
+            format: self.clip_format,
+        });
+
+        let parallel = (self.workers > 1).then_some(ParallelOptions {
+            workers: self.workers,
+            chunk_ms: self.chunk_secs * 1000,
+            overlap_ms: self.chunk_overlap_secs * 1000,
+        });
+
+        let vad = self.vad.then_some(VadOptions {
+            threshold_db: self.vad_threshold_db,
+            min_silence_ms: self.vad_min_silence_ms,
+            min_speech_ms: self.vad_min_speech_ms,
+            padding_ms: self.vad_padding_ms,
+            max_chunk_ms: self.vad_max_chunk_secs * 1000,
+            ..Default::default()
+        });
+
+        PipelineConfig::builder(self.workspace.unwrap_or_default())
+            .dirs(dirs)
+            .backend(self.backend)
+            .whisper_cli(self.whisper_cli)
+            .model_name(self.model_name)
+            .transcribe_options(transcribe)
+            .force(match &self.force {
+                Some(artifacts) if artifacts.is_empty() => Artifact::OUTPUTS.to_vec(),
+                Some(artifacts) => artifacts.clone(),
+                None => Vec::new(),
+            })
+            .archive_previous(self.archive_previous)
+            .restart(self.restart)
+            .translate(self.translate)
+            .translator(translator)
+            .outputs(self.outputs)
+            .bilingual_layout(self.bilingual_layout)
+            .parallel(parallel)
+            .vad(vad)
+            .language(LanguageOptions {
+                target: self.filter_language,
+                candidates: self.candidate_languages,
+                script_ratio: self.script_ratio,
+                min_confidence: self.min_confidence,
+            })
+            .split_mixed(self.split_mixed)
+            .global_dedup(self.global_dedup)
+            .dedup(DedupOptions {
+                method: self.dedup,
+                threshold: self.dedup_threshold,
+                expand_contractions: self.expand_contractions,
+                keep: self.dedup_keep,
+            })
+            .clips(clips)
+            .snapshots(snapshots)
+            .anki(anki)
+            .existing_subtitles(self.existing_subtitles)
+            .subtitle_formats(self.subtitle_formats)
+            .from_transcripts(self.from_transcripts)
+            .build()
     }
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
     let args = Args::parse();
-    
+
+    // -w 可以写在子命令之前或之后，因此不能由 clap 检查是否必需
+    let Some(workspace) = args.workspace.clone() else {
+        Args::command()
+            .error(
+                ErrorKind::MissingRequiredArgument,
+                "the following required arguments were not provided:\n  --workspace <WORKSPACE_DIR>",
+            )
+            .exit()
+    };
+
+    if args.command == Some(Command::Init) {
+        return init(&workspace);
+    }
+    if args.command == Some(Command::Doctor) {
+        return doctor(&workspace).await;
+    }
+
     // 验证workspace目录存在
-    if !args.workspace.exists() {
-        return Err(anyhow!("Workspace directory does not exist: {}", args.workspace.display()));
+    if !workspace.exists() {
+        return Err(anyhow!("Workspace directory does not exist: {}", workspace.display()));
     }
 
-    let processor = Video2En::new(args)?;
+    // workspace 中的 video2en.toml 提供参数默认值。要先解析一次命令行才知道 -w 指向哪个 workspace，
+    // 而 clap 只能在解析前设置默认值，所以读取配置后再解析一次，让命令行中没有给出的参数取配置文件的值
+    let workspace_config = WorkspaceConfig::load(&workspace)?;
+    let args = Args::parse_with_config(&workspace_config);
+    if args.print_config {
+        print!("{}", args.effective_config(&workspace_config).to_toml()?);
+        return Ok(());
+    }
+
+    let retry_failed_translations = args.retry_failed_translations;
+    let processor = Pipeline::new(args.into_config(workspace_config.dirs));
+
+    if retry_failed_translations {
+        return processor.retry_failed_translations().await;
+    }
     
-    // 验证输入文件和模型文件存在（这些验证现在在get_input_files和get_model_file中进行）
-    let _input_files = processor.get_input_files()?;
-    let _model_file = processor.get_model_file(processor.args.model_name.clone())?;
+    // 验证输入文件和模型文件存在（这些验证现在在input_files和model_file中进行）
+    let _input_files = processor.input_files()?;
+    if !processor.config().from_transcripts {
+        let _model_file = processor.model_file()?;
+    }
     
     processor.run().await
 }
 
+/// 检查运行环境；workspace 不存在或 video2en.toml 无效时作为失败项报告，其余检查照常进行
+async fn doctor(workspace: &std::path::Path) -> Result<()> {
+    let (mut config_check, mut config) = check_config(workspace);
+    let args = match Args::try_parse_with_config(&config) {
+        Ok(args) => args,
+        Err(e) if config_check.status == CheckStatus::Ok => {
+            // 配置文件中的值不是有效的参数值；不用配置文件重新解析，命令行参数本身有错时仍然报错退出
+            let message = e.to_string();
+            let error = message.lines().next().unwrap_or_default().trim_start_matches("error: ");
+            config_check = Check::fail(
+                "配置文件",
+                format!("{} 中的值无效: {}", WorkspaceConfig::path(workspace).display(), error),
+                "修正其中的错误，或删除后运行 video2en init 重新生成示例配置",
+            );
+            config = WorkspaceConfig::default();
+            Args::parse_with_config(&config)
+        }
+        Err(e) => e.exit(),
+    };
+
+    let mut checks = vec![config_check];
+    checks.extend(run_checks(&args.doctor_options(&config)).await);
+    let failed = print_report(&checks);
+    if failed > 0 {
+        return Err(anyhow!("{} checks failed", failed));
+    }
+    Ok(())
+}
+
+/// 创建workspace目录结构和示例配置
+fn init(workspace: &std::path::Path) -> Result<()> {
+    let created = init_workspace(workspace)?;
+    if created.is_empty() {
+        println!("✅ 工作区已完整，无需创建: {}", workspace.display());
+    } else {
+        println!("📁 已创建:");
+        for path in &created {
+            println!("   - {}", path.display());
+        }
+    }
+
+    let dirs = WorkspaceConfig::load(workspace)?.dirs;
+    println!("\n下一步:");
+    println!("   1. 把视频/音频文件放入 {}", workspace.join(&dirs.video2en_input).display());
+    println!(
+        "   2. 下载模型到 {}，如: curl -L -o {} https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-large.bin",
+        workspace.join(&dirs.models).display(),
+        workspace.join(&dirs.models).join("ggml-large.bin").display()
+    );
+    println!("   3. 按需修改 {}", WorkspaceConfig::path(workspace).display());
+    println!("   4. 运行 video2en doctor -w {} 检查环境", workspace.display());
+    Ok(())
+}
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use video2en::youdao::YoudaoTranslator;
+
+    #[test]
+    fn test_transcribe_args() {
+        let config = Args::parse_from(["video2en", "-w", "ws", "--threads", "6"]).into_config(WorkspaceDirs::default());
+        assert_eq!(config.backend, Backend::WhisperCli);
+        assert_eq!(config.whisper_cli, "whisper-cli");
+        assert_eq!(config.model_name, None);
+        let transcribe = &config.transcribe;
+        assert_eq!((transcribe.language.as_str(), transcribe.threads), ("auto", 6));
+        assert_eq!((transcribe.beam_size, transcribe.best_of, transcribe.temperature), (8, 1, 0.0));
+        assert!(!transcribe.use_gpu && !transcribe.translate && transcribe.initial_prompt.is_none());
+
+        let config = Args::parse_from([
+            "video2en", "-w", "ws", "--backend", "whisper-rs", "--whisper-cli", "/opt/whisper/main",
+            "--model-name", "ggml-base.bin", "--language", "ja", "--beam-size", "3", "--best-of", "2",
+            "--temperature", "0.4", "--initial-prompt", "Tokyo", "--whisper-translate", "--gpu", "--gpu-device", "1",
+        ])
+        .into_config(WorkspaceDirs::default());
+        assert_eq!(config.backend, Backend::WhisperRs);
+        assert_eq!(config.whisper_cli, "/opt/whisper/main");
+        assert_eq!(config.model_name.as_deref(), Some("ggml-base.bin"));
+        let transcribe = &config.transcribe;
+        assert_eq!(transcribe.language, "ja");
+        assert_eq!((transcribe.beam_size, transcribe.best_of, transcribe.temperature), (3, 2, 0.4));
+        assert_eq!(transcribe.initial_prompt.as_deref(), Some("Tokyo"));
+        assert!(transcribe.translate);
+        assert_eq!((transcribe.use_gpu, transcribe.gpu_device), (true, 1));
+    }
+
+    #[test]
+    fn test_chunk_args() {
+        let args = Args::parse_from(["video2en", "-w", "ws", "--workers", "4", "--chunk-secs", "86400"]);
+        assert!(args.check_chunk_overlap().is_ok());
+        let parallel = args.into_config(WorkspaceDirs::default()).parallel.unwrap();
+        assert_eq!((parallel.chunk_ms, parallel.overlap_ms), (86_400_000, 5000));
+
+        // 换算为毫秒会溢出 u32 的值在解析时拒绝
+        for (flag, value) in [
+            ("--chunk-secs", "4294968"),
+            ("--chunk-secs", "0"),
+            ("--chunk-overlap-secs", "3601"),
+            ("--vad-max-chunk-secs", "4294968"),
+        ] {
+            assert!(Args::try_parse_from(["video2en", "-w", "ws", flag, value]).is_err(), "{} {}", flag, value);
+        }
+
+        let args = Args::parse_from(["video2en", "-w", "ws", "--chunk-secs", "10", "--chunk-overlap-secs", "10"]);
+        assert_eq!(args.check_chunk_overlap().unwrap_err().kind(), ErrorKind::ArgumentConflict);
+    }
 
     #[tokio::test]
     async fn test_translation() {
-        let translator = YoudaoTranslator;
+        let translator = YoudaoTranslator::new();
         
         let test_text = "It's peaceful".to_string();
         println!("📝 测试文本: {}", test_text);
@@ -793,4 +693,5 @@ mod test {
             }
         }
     }
+
 }
\ No newline at end of file
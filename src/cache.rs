@@ -0,0 +1,156 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs::{self, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crate::text::normalize_text;
+use crate::translator::Translation;
+
+/// 翻译缓存的使用方式
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheMode {
+    /// 优先读缓存，未命中时请求翻译服务并写入缓存
+    ReadWrite,
+    /// 只读缓存，不访问网络（离线模式）
+    CacheOnly,
+    /// 忽略缓存，总是请求翻译服务并刷新缓存
+    Refresh,
+    /// 不使用缓存
+    Off,
+}
+
+/// 缓存文件中的一行
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CacheEntry {
+    key: String,
+    provider: String,
+    target_lang: String,
+    /// 写入时间（Unix 秒）
+    created_at: u64,
+    translation: Translation,
+}
+
+/// 以 JSON-lines 存储在 workspace 中的翻译缓存，键为标准化文本 + 服务 + 目标语言
+pub struct TranslationCache {
+    path: PathBuf,
+    ttl: Option<Duration>,
+    entries: HashMap<(String, String, String), CacheEntry>,
+}
+
+impl TranslationCache {
+    /// 打开缓存文件，不存在时视为空缓存
+    pub fn open(path: &Path, ttl: Option<Duration>) -> Result<Self> {
+        let mut entries = HashMap::new();
+
+        if path.exists() {
+            let content = fs::read_to_string(path)
+                .context(format!("Failed to read translation cache: {}", path.display()))?;
+            // 同一键后写入的行覆盖先写入的行；损坏的行直接跳过
+            for entry in content
+                .lines()
+                .filter_map(|line| serde_json::from_str::<CacheEntry>(line).ok())
+            {
+                entries.insert(
+                    (entry.provider.clone(), entry.target_lang.clone(), entry.key.clone()),
+                    entry,
+                );
+            }
+        }
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            ttl,
+            entries,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// 查询未过期的缓存结果
+    pub fn get(&self, provider: &str, target_lang: &str, text: &str) -> Option<Translation> {
+        let key = (provider.to_string(), target_lang.to_string(), normalize_text(text));
+        let entry = self.entries.get(&key)?;
+
+        if let Some(ttl) = self.ttl {
+            if now_secs().saturating_sub(entry.created_at) >= ttl.as_secs() {
+                return None;
+            }
+        }
+
+        Some(entry.translation.clone())
+    }
+
+    /// 写入缓存并追加到缓存文件
+    pub fn insert(&mut self, provider: &str, target_lang: &str, text: &str, translation: &Translation) -> Result<()> {
+        let entry = CacheEntry {
+            key: normalize_text(text),
+            provider: provider.to_string(),
+            target_lang: target_lang.to_string(),
+            created_at: now_secs(),
+            translation: translation.clone(),
+        };
+
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).context("Failed to create cache directory")?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .context(format!("Failed to open translation cache: {}", self.path.display()))?;
+        writeln!(file, "{}", serde_json::to_string(&entry)?)
+            .context("Failed to write translation cache")?;
+
+        self.entries.insert(
+            (entry.provider.clone(), entry.target_lang.clone(), entry.key.clone()),
+            entry,
+        );
+        Ok(())
+    }
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_cache_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("translations.jsonl");
+        let translation = Translation {
+            translation: Some("我完蛋了".to_string()),
+            ..Default::default()
+        };
+
+        let mut cache = TranslationCache::open(&path, None).unwrap();
+        cache.insert("youdao", "zh", "I'm screwed.", &translation).unwrap();
+
+        // 重新打开后按标准化文本命中，服务或语言不同则不命中
+        let cache = TranslationCache::open(&path, None).unwrap();
+        let hit = cache.get("youdao", "zh", "  i'm SCREWED ").unwrap();
+        assert_eq!(hit.translation.as_deref(), Some("我完蛋了"));
+        assert!(cache.get("libre", "zh", "I'm screwed.").is_none());
+        assert!(cache.get("youdao", "ja", "I'm screwed.").is_none());
+
+        let expired = TranslationCache::open(&path, Some(Duration::ZERO)).unwrap();
+        assert!(expired.get("youdao", "zh", "I'm screwed.").is_none());
+    }
+}
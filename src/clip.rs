@@ -0,0 +1,206 @@
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use crate::audio::{read_wav_samples, write_wav_samples, WHISPER_SAMPLE_RATE};
+use crate::segment::Segment;
+
+/// 句子音频片段的编码格式
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ClipFormat {
+    Wav,
+    /// 需要 ffmpeg 支持 libmp3lame
+    Mp3,
+    /// 需要 ffmpeg 支持 libvorbis
+    Ogg,
+}
+
+impl ClipFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ClipFormat::Wav => "wav",
+            ClipFormat::Mp3 => "mp3",
+            ClipFormat::Ogg => "ogg",
+        }
+    }
+}
+
+/// 句子音频片段的切割参数
+#[derive(Debug, Clone)]
+pub struct ClipOptions {
+    /// 片段前后额外保留的时长
+    pub padding_ms: u32,
+    /// 淡入淡出时长
+    pub fade_ms: u32,
+    pub format: ClipFormat,
+}
+
+impl Default for ClipOptions {
+    fn default() -> Self {
+        Self {
+            padding_ms: 200,
+            fade_ms: 30,
+            format: ClipFormat::Wav,
+        }
+    }
+}
+
+/// 清单中的一个片段
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Clip {
+    /// 片段文件，相对清单所在目录
+    pub file: PathBuf,
+    /// segment 的时间轴
+    pub start_ms: u32,
+    pub end_ms: u32,
+    /// 加上前后留白后实际切出的范围
+    pub clip_start_ms: u32,
+    pub clip_end_ms: u32,
+    pub text: String,
+    pub translation: Option<String>,
+}
+
+/// `<name>.clips.json`：片段与文本、翻译的对应关系
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClipManifest {
+    /// 切割来源的16kHz WAV
+    pub audio_path: PathBuf,
+    pub format: ClipFormat,
+    pub clips: Vec<Clip>,
+}
+
+impl ClipManifest {
+    pub fn read(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .context(format!("Failed to read clip manifest: {}", path.display()))?;
+        serde_json::from_str(&content).context(format!("Invalid clip manifest: {}", path.display()))
+    }
+}
+
+/// 片段目录 `<name>.clips/` 与清单 `<name>.clips.json`，与音频文件同目录
+pub fn clip_paths(audio_path: &Path) -> (PathBuf, PathBuf) {
+    let stem = audio_path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+    (
+        audio_path.with_file_name(format!("{}.clips", stem)),
+        audio_path.with_file_name(format!("{}.clips.json", stem)),
+    )
+}
+
+/// 为每个segment从音频中切出一个片段，写入片段目录并返回清单
+pub fn extract_clips(audio_path: &Path, segments: &[Segment], options: &ClipOptions) -> Result<ClipManifest> {
+    let (clip_dir, _) = clip_paths(audio_path);
+    fs::create_dir_all(&clip_dir).context(format!("Failed to create clip directory: {}", clip_dir.display()))?;
+
+    let samples = read_wav_samples(audio_path)?;
+    let total_ms = (samples.len() as u64 * 1000 / WHISPER_SAMPLE_RATE as u64) as u32;
+    let clip_dir_name = PathBuf::from(clip_dir.file_name().unwrap_or_default());
+
+    let mut clips = Vec::new();
+    for (i, segment) in segments.iter().enumerate() {
+        let clip_start_ms = segment.start_ms.saturating_sub(options.padding_ms);
+        let clip_end_ms = segment.end_ms.saturating_add(options.padding_ms).min(total_ms);
+        if clip_end_ms <= clip_start_ms {
+            continue;
+        }
+
+        let file_name = format!("{:04}.{}", i + 1, options.format.extension());
+        let clip = cut_clip(&samples, clip_start_ms, clip_end_ms, options.fade_ms);
+        write_clip(&clip_dir.join(&file_name), &clip, options.format)?;
+
+        clips.push(Clip {
+            file: clip_dir_name.join(&file_name),
+            start_ms: segment.start_ms,
+            end_ms: segment.end_ms,
+            clip_start_ms,
+            clip_end_ms,
+            text: segment.text.clone(),
+            translation: segment.translation.clone(),
+        });
+    }
+
+    Ok(ClipManifest {
+        audio_path: audio_path.to_path_buf(),
+        format: options.format,
+        clips,
+    })
+}
+
+/// 切出 `[start_ms, end_ms)` 的采样，并在首尾做线性淡入淡出
+pub fn cut_clip(samples: &[f32], start_ms: u32, end_ms: u32, fade_ms: u32) -> Vec<f32> {
+    let to_index = |ms: u32| ((ms as u64 * WHISPER_SAMPLE_RATE as u64 / 1000) as usize).min(samples.len());
+    let mut clip = samples[to_index(start_ms)..to_index(end_ms)].to_vec();
+
+    let fade = ((fade_ms as u64 * WHISPER_SAMPLE_RATE as u64 / 1000) as usize).min(clip.len() / 2);
+    let len = clip.len();
+    for i in 0..fade {
+        let gain = i as f32 / fade as f32;
+        clip[i] *= gain;
+        clip[len - 1 - i] *= gain;
+    }
+    clip
+}
+
+/// WAV 直接写出；mp3/ogg 先写临时 WAV 再用 ffmpeg 编码
+fn write_clip(path: &Path, samples: &[f32], format: ClipFormat) -> Result<()> {
+    if format == ClipFormat::Wav {
+        return write_wav_samples(path, samples, WHISPER_SAMPLE_RATE);
+    }
+
+    let wav_path = path.with_extension("tmp.wav");
+    write_wav_samples(&wav_path, samples, WHISPER_SAMPLE_RATE)?;
+
+    let codec = match format {
+        ClipFormat::Mp3 => "libmp3lame",
+        _ => "libvorbis",
+    };
+    let status = Command::new("ffmpeg")
+        .args(["-y", "-loglevel", "error"])
+        .arg("-i").arg(&wav_path)
+        .args(["-c:a", codec, "-q:a", "4"])
+        .arg(path)
+        .status()
+        .context("Failed to execute ffmpeg")?;
+    fs::remove_file(&wav_path).ok();
+
+    if !status.success() {
+        return Err(anyhow!("ffmpeg failed to encode {} with exit code: {}", path.display(), status));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_extract_clips() {
+        let dir = tempfile::tempdir().unwrap();
+        let audio_path = dir.path().join("movie.wav");
+        // 2秒的恒定信号
+        write_wav_samples(&audio_path, &vec![0.5; 2 * WHISPER_SAMPLE_RATE as usize], WHISPER_SAMPLE_RATE).unwrap();
+
+        let mut segment = Segment::new(100, 1900, "Take it easy.");
+        segment.translation = Some("放轻松".to_string());
+        let options = ClipOptions {
+            padding_ms: 200,
+            fade_ms: 10,
+            format: ClipFormat::Wav,
+        };
+        let manifest = extract_clips(&audio_path, &[segment], &options).unwrap();
+
+        let clip = &manifest.clips[0];
+        assert_eq!((clip.clip_start_ms, clip.clip_end_ms), (0, 2000));
+        assert_eq!(clip.file, PathBuf::from("movie.clips/0001.wav"));
+        assert_eq!(clip.translation.as_deref(), Some("放轻松"));
+
+        let samples = read_wav_samples(&dir.path().join(&clip.file)).unwrap();
+        assert_eq!(samples.len(), 2 * WHISPER_SAMPLE_RATE as usize);
+        assert_eq!(samples[0], 0.0);
+        assert!((samples[WHISPER_SAMPLE_RATE as usize] - 0.5).abs() < 0.001);
+    }
+}
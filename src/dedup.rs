@@ -0,0 +1,231 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::text::normalize_text;
+use crate::segment::{MergedSegment, Segment};
+
+/// 判断两句是否重复的方法
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DedupMethod {
+    /// 标准化文本完全相同
+    Exact,
+    /// 词级编辑距离：1 - 距离 / 较长句子的词数
+    EditDistance,
+    /// 词集合的 Jaccard 相似度
+    Jaccard,
+}
+
+/// 一组重复句子中保留哪一句
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeepStrategy {
+    /// 最早出现的一句
+    Earliest,
+    /// 文本最长的一句
+    Longest,
+}
+
+/// 去重配置
+#[derive(Debug, Clone)]
+pub struct DedupOptions {
+    pub method: DedupMethod,
+    /// 相似度不低于该值即视为重复（`Exact` 不使用）
+    pub threshold: f64,
+    /// 比较前展开缩写，如 "I'm" → "i am"
+    pub expand_contractions: bool,
+    pub keep: KeepStrategy,
+}
+
+impl Default for DedupOptions {
+    fn default() -> Self {
+        Self {
+            method: DedupMethod::Exact,
+            threshold: 0.8,
+            expand_contractions: false,
+            keep: KeepStrategy::Earliest,
+        }
+    }
+}
+
+/// 在 `candidates` 指定的segments中标记重复：每组只保留一句，其余标记 `is_duplicate`，
+/// 被合并的句子记录在保留句的 `merged` 中
+pub fn mark_duplicates(segments: &mut [Segment], candidates: &[usize], options: &DedupOptions) {
+    let texts: Vec<&str> = candidates.iter().map(|&i| segments[i].text.as_str()).collect();
+    let groups = group_duplicates(&texts, options);
+
+    for group in groups {
+        let members: Vec<(usize, f64)> = group.iter().map(|&(i, similarity)| (candidates[i], similarity)).collect();
+        let keep = match options.keep {
+            KeepStrategy::Earliest => members[0].0,
+            // 长度相同时保留较早的一句
+            KeepStrategy::Longest => members
+                .iter()
+                .map(|&(i, _)| i)
+                .max_by_key(|&i| (segments[i].text.chars().count(), std::cmp::Reverse(i)))
+                .unwrap_or(members[0].0),
+        };
+
+        let mut merged = Vec::new();
+        for &(i, similarity) in &members {
+            segments[i].is_duplicate = i != keep;
+            if i != keep {
+                merged.push(MergedSegment {
+                    start_ms: segments[i].start_ms,
+                    end_ms: segments[i].end_ms,
+                    text: segments[i].text.clone(),
+                    similarity,
+                });
+            }
+        }
+        segments[keep].merged = merged;
+    }
+}
+
+/// 按出现顺序把文本分组，返回每组成员的下标及其与组内第一句的相似度
+pub fn group_duplicates(texts: &[&str], options: &DedupOptions) -> Vec<Vec<(usize, f64)>> {
+    let mut groups: Vec<Vec<(usize, f64)>> = Vec::new();
+    // 每组第一句的词序列，以及完全相同文本的快速索引
+    let mut heads: Vec<Vec<String>> = Vec::new();
+    let mut exact: HashMap<Vec<String>, usize> = HashMap::new();
+
+    for (i, text) in texts.iter().enumerate() {
+        let tokens = tokenize(text, options.expand_contractions);
+
+        let mut found = exact.get(&tokens).map(|&group| (group, 1.0));
+        if found.is_none() && options.method != DedupMethod::Exact {
+            found = heads
+                .iter()
+                .enumerate()
+                .map(|(group, head)| (group, similarity(head, &tokens, options.method)))
+                .filter(|&(_, score)| score >= options.threshold)
+                .max_by(|a, b| a.1.total_cmp(&b.1));
+        }
+
+        match found {
+            Some((group, score)) => groups[group].push((i, score)),
+            None => {
+                exact.insert(tokens.clone(), groups.len());
+                heads.push(tokens);
+                groups.push(vec![(i, 1.0)]);
+            }
+        }
+    }
+
+    groups
+}
+
+/// 两个词序列的相似度（0~1）
+pub fn similarity(a: &[String], b: &[String], method: DedupMethod) -> f64 {
+    if a.is_empty() && b.is_empty() {
+        return 1.0;
+    }
+    match method {
+        DedupMethod::Exact => (a == b) as u8 as f64,
+        DedupMethod::EditDistance => {
+            let longest = a.len().max(b.len());
+            1.0 - edit_distance(a, b) as f64 / longest as f64
+        }
+        DedupMethod::Jaccard => {
+            let a: HashSet<&String> = a.iter().collect();
+            let b: HashSet<&String> = b.iter().collect();
+            a.intersection(&b).count() as f64 / a.union(&b).count() as f64
+        }
+    }
+}
+
+/// 词级 Levenshtein 距离
+fn edit_distance(a: &[String], b: &[String]) -> usize {
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, token_a) in a.iter().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, token_b) in b.iter().enumerate() {
+            let substitution = previous[j] + (token_a != token_b) as usize;
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// 转为小写词序列，可选先展开缩写
+fn tokenize(text: &str, expand_contractions: bool) -> Vec<String> {
+    let text = text.to_lowercase().replace('’', "'");
+    let text = if expand_contractions {
+        text.split_whitespace().map(expand_contraction).collect::<Vec<_>>().join(" ")
+    } else {
+        text
+    };
+    normalize_text(&text).split_whitespace().map(str::to_string).collect()
+}
+
+fn expand_contraction(word: &str) -> String {
+    let trimmed = word.trim_matches(|c: char| !c.is_alphanumeric() && c != '\'');
+    let expanded = match trimmed {
+        "can't" => "can not".to_string(),
+        "won't" => "will not".to_string(),
+        "shan't" => "shall not".to_string(),
+        "ain't" => "is not".to_string(),
+        "i'm" => "i am".to_string(),
+        "let's" => "let us".to_string(),
+        _ => {
+            const SUFFIXES: &[(&str, &str)] = &[
+                ("n't", " not"),
+                ("'re", " are"),
+                ("'ve", " have"),
+                ("'ll", " will"),
+                ("'d", " would"),
+                ("'s", " is"),
+            ];
+            match SUFFIXES.iter().find(|(suffix, _)| trimmed.ends_with(suffix)) {
+                // 's 只对代词和疑问词展开，避免把所有格 "john's" 变成 "john is"
+                Some(("'s", _)) if !is_s_contraction(trimmed) => trimmed.to_string(),
+                Some((suffix, expansion)) => format!("{}{}", &trimmed[..trimmed.len() - suffix.len()], expansion),
+                None => trimmed.to_string(),
+            }
+        }
+    };
+    word.replace(trimmed, &expanded)
+}
+
+fn is_s_contraction(word: &str) -> bool {
+    matches!(
+        word,
+        "it's" | "that's" | "what's" | "he's" | "she's" | "there's" | "here's" | "where's" | "who's" | "how's"
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_fuzzy_dedup() {
+        let mut segments = vec![
+            Segment::new(0, 1000, "I'm screwed"),
+            Segment::new(1000, 2000, "I am screwed."),
+            Segment::new(2000, 3000, "We are so totally screwed!"),
+            Segment::new(3000, 4000, "We are totally screwed"),
+            Segment::new(4000, 5000, "John's car"),
+        ];
+        let candidates: Vec<usize> = (0..segments.len()).collect();
+
+        // 默认只合并完全相同的文本
+        let mut exact = segments.clone();
+        mark_duplicates(&mut exact, &candidates, &DedupOptions::default());
+        assert!(exact.iter().all(|s| !s.is_duplicate));
+
+        let options = DedupOptions {
+            method: DedupMethod::EditDistance,
+            threshold: 0.75,
+            expand_contractions: true,
+            keep: KeepStrategy::Longest,
+        };
+        mark_duplicates(&mut segments, &candidates, &options);
+        let kept: Vec<&str> = segments.iter().filter(|s| !s.is_duplicate).map(|s| s.text.as_str()).collect();
+        assert_eq!(kept, vec!["I am screwed.", "We are so totally screwed!", "John's car"]);
+        assert_eq!(segments[1].merged[0].text, "I'm screwed");
+        assert_eq!(segments[2].merged[0].start_ms, 3000);
+        assert_eq!(segments[2].merged[0].similarity, 0.8);
+
+        let words = |text: &str| tokenize(text, false);
+        assert_eq!(similarity(&words("a b c"), &words("c b a d"), DedupMethod::Jaccard), 0.75);
+    }
+}
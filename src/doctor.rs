@@ -0,0 +1,552 @@
+use anyhow::{anyhow, Context, Result};
+use reqwest::Client;
+use std::{
+    fs::{self, File},
+    io::Read,
+    path::{Path, PathBuf},
+    process::Command,
+    time::Duration,
+};
+
+use crate::toolchain::{ffmpeg_install_hint, platform_hint, resolve_model_file, whisper_cli_install_hint, Backend};
+use crate::translator::{DictTranslator, Provider, TranslatorConfig};
+use crate::workspace::{WorkspaceConfig, WorkspaceDirs};
+use crate::youdao::YOUDAO_BASE_URL;
+
+/// whisper.cpp 模型文件开头的魔数 `ggml`
+const GGML_MAGIC: u32 = 0x6767_6d6c;
+
+/// 单项检查结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckStatus {
+    Ok,
+    /// 不影响基本流程，但部分功能不可用
+    Warn,
+    Fail,
+}
+
+/// 一项环境检查
+#[derive(Debug, Clone)]
+pub struct Check {
+    pub name: &'static str,
+    pub status: CheckStatus,
+    pub detail: String,
+    /// 如何修复
+    pub remediation: Option<String>,
+}
+
+impl Check {
+    pub fn ok(name: &'static str, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: CheckStatus::Ok,
+            detail: detail.into(),
+            remediation: None,
+        }
+    }
+
+    pub fn warn(name: &'static str, detail: impl Into<String>, remediation: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: CheckStatus::Warn,
+            detail: detail.into(),
+            remediation: Some(remediation.into()),
+        }
+    }
+
+    pub fn fail(name: &'static str, detail: impl Into<String>, remediation: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: CheckStatus::Fail,
+            detail: detail.into(),
+            remediation: Some(remediation.into()),
+        }
+    }
+}
+
+/// `doctor` 检查的对象，取自合并 video2en.toml 后的参数
+#[derive(Debug, Clone)]
+pub struct DoctorOptions {
+    pub workspace: PathBuf,
+    pub dirs: WorkspaceDirs,
+    pub backend: Backend,
+    pub whisper_cli: String,
+    pub model_name: String,
+    pub translator: TranslatorConfig,
+    pub tts_url: String,
+}
+
+/// 读取 workspace 中的 video2en.toml；无法读取或解析时返回失败项和默认配置，其余检查照常进行
+pub fn check_config(workspace: &Path) -> (Check, WorkspaceConfig) {
+    let path = WorkspaceConfig::path(workspace);
+    if !path.exists() {
+        return (Check::ok("配置文件", format!("{} 不存在，使用内置默认值", path.display())), WorkspaceConfig::default());
+    }
+    match WorkspaceConfig::load(workspace) {
+        Ok(config) => (Check::ok("配置文件", path.display().to_string()), config),
+        Err(e) => (
+            Check::fail("配置文件", format!("{:#}", e), "修正其中的错误，或删除后运行 video2en init 重新生成示例配置"),
+            WorkspaceConfig::default(),
+        ),
+    }
+}
+
+/// 依次检查 workspace、ffmpeg、识别后端、模型文件、翻译服务和 TTS 服务
+pub async fn run_checks(options: &DoctorOptions) -> Vec<Check> {
+    let mut checks = check_workspace(options);
+    checks.push(check_ffmpeg());
+    checks.push(check_ffmpeg_encoders());
+    checks.push(check_ffprobe());
+    checks.push(check_backend(options));
+    checks.push(check_model(options));
+
+    let client = Client::builder()
+        .timeout(Duration::from_secs(5))
+        .build()
+        .unwrap_or_default();
+    checks.push(check_translator(&client, &options.translator).await);
+    checks.push(check_tts(&client, &options.tts_url).await);
+    checks
+}
+
+/// 打印检查报告，返回失败的项数
+pub fn print_report(checks: &[Check]) -> usize {
+    for check in checks {
+        let icon = match check.status {
+            CheckStatus::Ok => "✅",
+            CheckStatus::Warn => "⚠️",
+            CheckStatus::Fail => "❌",
+        };
+        println!("{} {}: {}", icon, check.name, check.detail);
+        if let Some(remediation) = &check.remediation {
+            for line in remediation.lines() {
+                println!("   👉 {}", line);
+            }
+        }
+    }
+
+    let failed = checks.iter().filter(|c| c.status == CheckStatus::Fail).count();
+    let warned = checks.iter().filter(|c| c.status == CheckStatus::Warn).count();
+    println!("\n📊 检查结果: 通过 {}，警告 {}，失败 {}", checks.len() - failed - warned, warned, failed);
+    failed
+}
+
+fn check_workspace(options: &DoctorOptions) -> Vec<Check> {
+    let init_hint = format!("运行 video2en init -w {} 创建工作区目录和示例配置", options.workspace.display());
+    if !options.workspace.is_dir() {
+        return vec![Check::fail("工作区", format!("{} 不存在", options.workspace.display()), init_hint)];
+    }
+    let dirs = &options.dirs;
+    let mut checks = Vec::new();
+
+    for (name, dir, required) in [
+        ("video2en 输入目录", &dirs.video2en_input, true),
+        ("模型目录", &dirs.models, true),
+        ("txt2audio 输入目录", &dirs.txt2audio_input, false),
+    ] {
+        let path = options.workspace.join(dir);
+        checks.push(if path.is_dir() {
+            Check::ok(name, path.display().to_string())
+        } else if required {
+            Check::fail(name, format!("{} 不存在", path.display()), init_hint.clone())
+        } else {
+            Check::warn(name, format!("{} 不存在（只影响 txt2audio）", path.display()), init_hint.clone())
+        });
+    }
+    checks
+}
+
+/// 运行命令并返回标准输出
+fn command_output(program: &str, args: &[&str]) -> Result<String> {
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .context(format!("Failed to run {}", program))?;
+    if !output.status.success() {
+        return Err(anyhow!("{} exited with {}", program, output.status));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+fn check_ffmpeg() -> Check {
+    let Ok(path) = which::which("ffmpeg") else {
+        return Check::fail("ffmpeg", "未在 PATH 中找到 ffmpeg（用于提取音频）", ffmpeg_install_hint());
+    };
+    match command_output("ffmpeg", &["-hide_banner", "-version"]) {
+        Ok(output) => {
+            let version = output.lines().next().unwrap_or_default().trim().to_string();
+            Check::ok("ffmpeg", format!("{} ({})", version, path.display()))
+        }
+        Err(e) => Check::fail("ffmpeg", format!("{} 无法运行: {:#}", path.display(), e), ffmpeg_install_hint()),
+    }
+}
+
+/// 检查提取音频、句子片段和截图用到的编码器
+fn check_ffmpeg_encoders() -> Check {
+    const REQUIRED: &[(&str, &str)] = &[("pcm_s16le", "WAV 音频")];
+    const OPTIONAL: &[(&str, &str)] = &[
+        ("libmp3lame", "--clip-format mp3"),
+        ("libvorbis", "--clip-format ogg"),
+        ("mjpeg", "--snapshot-format jpg"),
+        ("png", "--snapshot-format png"),
+        ("libwebp", "--snapshot-format webp"),
+    ];
+
+    let Ok(output) = command_output("ffmpeg", &["-hide_banner", "-encoders"]) else {
+        return Check::fail("ffmpeg 编码器", "无法列出 ffmpeg 编码器", ffmpeg_install_hint());
+    };
+    // 每行形如 " A....D pcm_s16le  PCM signed 16-bit little-endian"
+    let encoders: Vec<&str> = output
+        .lines()
+        .filter_map(|line| line.split_whitespace().nth(1))
+        .collect();
+    let has = |name: &str| encoders.contains(&name);
+
+    let found: Vec<&str> = REQUIRED
+        .iter()
+        .chain(OPTIONAL)
+        .map(|(name, _)| *name)
+        .filter(|name| has(name))
+        .collect();
+    let missing_required: Vec<String> = REQUIRED
+        .iter()
+        .filter(|(name, _)| !has(name))
+        .map(|(name, usage)| format!("{}（{}）", name, usage))
+        .collect();
+    let missing_optional: Vec<String> = OPTIONAL
+        .iter()
+        .filter(|(name, _)| !has(name))
+        .map(|(name, usage)| format!("{}（{}）", name, usage))
+        .collect();
+
+    let full_build_hint = platform_hint(
+        "发行版精简版的 ffmpeg 可能缺少部分编码器：Fedora 启用 RPM Fusion 后安装完整版 ffmpeg，\n\
+         或使用静态构建 https://johnvansickle.com/ffmpeg/",
+        "brew reinstall ffmpeg",
+        "安装 full 版本的 ffmpeg 构建（如 https://www.gyan.dev/ffmpeg/builds/ 的 full build）",
+    );
+    if !missing_required.is_empty() {
+        Check::fail("ffmpeg 编码器", format!("缺少 {}", missing_required.join("、")), full_build_hint)
+    } else if !missing_optional.is_empty() {
+        Check::warn(
+            "ffmpeg 编码器",
+            format!("可用: {}；缺少 {}", found.join(", "), missing_optional.join("、")),
+            full_build_hint,
+        )
+    } else {
+        Check::ok("ffmpeg 编码器", found.join(", "))
+    }
+}
+
+fn check_ffprobe() -> Check {
+    match which::which("ffprobe") {
+        Ok(path) => Check::ok("ffprobe", path.display().to_string()),
+        Err(_) => Check::warn(
+            "ffprobe",
+            "未在 PATH 中找到 ffprobe，无法导入内嵌字幕，截图时只能按扩展名判断是否有视频",
+            "ffprobe 通常随 ffmpeg 一起安装:\n".to_string() + &ffmpeg_install_hint(),
+        ),
+    }
+}
+
+fn check_backend(options: &DoctorOptions) -> Check {
+    match options.backend {
+        Backend::WhisperCli => match which::which(&options.whisper_cli) {
+            Ok(path) => Check::ok("识别后端", format!("whisper-cli ({})", path.display())),
+            Err(_) => Check::fail(
+                "识别后端",
+                format!("未找到 {}", options.whisper_cli),
+                whisper_cli_install_hint(),
+            ),
+        },
+        Backend::WhisperRs if cfg!(feature = "whisper-rs") => Check::ok("识别后端", "whisper-rs（进程内）"),
+        Backend::WhisperRs => Check::fail(
+            "识别后端",
+            "当前构建未启用 whisper-rs feature",
+            platform_hint(
+                "sudo apt install cmake clang（Fedora: sudo dnf install cmake clang）\n\
+                 cargo build --release --features whisper-rs",
+                "brew install cmake llvm\ncargo build --release --features whisper-rs",
+                "安装 cmake 和 LLVM（winget install Kitware.CMake LLVM.LLVM）\n\
+                 cargo build --release --features whisper-rs",
+            ),
+        ),
+    }
+}
+
+fn check_model(options: &DoctorOptions) -> Check {
+    let models_dir = options.workspace.join(&options.dirs.models);
+    let path = models_dir.join(&options.model_name);
+
+    // 与处理时使用同样的查找规则：指定的模型不存在时使用目录中唯一的模型
+    if let Ok(resolved) = resolve_model_file(&models_dir, &options.model_name) {
+        let mut check = check_model_file(&resolved);
+        if resolved != path {
+            check.detail = format!("{} 不存在，使用模型目录中唯一的模型 {}", path.display(), check.detail);
+        }
+        return check;
+    }
+
+    let available: Vec<String> = fs::read_dir(&models_dir)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.file_name().to_string_lossy().to_string())
+                .filter(|name| name.ends_with(".bin"))
+                .collect()
+        })
+        .unwrap_or_default();
+    let detail = if available.is_empty() {
+        format!("{} 不存在，模型目录中没有 .bin 文件", path.display())
+    } else {
+        format!("{} 不存在，模型目录中有多个模型: {}", path.display(), available.join(", "))
+    };
+    Check::fail(
+        "模型文件",
+        detail,
+        format!(
+            "curl -L -o {} https://huggingface.co/ggerganov/whisper.cpp/resolve/main/{}\n\
+             或用 --model-name / video2en.toml 的 [video2en] model 选择已有模型",
+            path.display(),
+            options.model_name
+        ),
+    )
+}
+
+/// whisper.cpp GGML 模型的文件头
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GgmlHeader {
+    pub n_vocab: i32,
+    pub n_audio_layer: i32,
+    pub n_text_layer: i32,
+    pub n_mels: i32,
+    /// 0 = f32，1 = f16，其他为量化格式
+    pub ftype: i32,
+}
+
+impl GgmlHeader {
+    /// 模型规模名称和 f16 格式的参考大小
+    pub fn model_type(&self) -> Option<(&'static str, u64)> {
+        match (self.n_audio_layer, self.n_text_layer) {
+            (4, 4) => Some(("tiny", 77_691_713)),
+            (6, 6) => Some(("base", 147_951_465)),
+            (12, 12) => Some(("small", 487_601_967)),
+            (24, 24) => Some(("medium", 1_533_763_059)),
+            (32, 32) => Some(("large", 3_095_033_483)),
+            (32, 4) => Some(("large-v3-turbo", 1_624_555_275)),
+            _ => None,
+        }
+    }
+
+    fn ftype_name(&self) -> String {
+        // 量化模型的 ftype 带有 1000 倍的量化版本号
+        match self.ftype % 1000 {
+            0 => "f32".to_string(),
+            1 => "f16".to_string(),
+            other => format!("quantized ftype {}", other),
+        }
+    }
+}
+
+/// 读取 GGML 模型文件头：魔数之后依次为 11 个 i32 超参数
+pub fn read_ggml_header(path: &Path) -> Result<GgmlHeader> {
+    let mut file = File::open(path).context(format!("Failed to open model: {}", path.display()))?;
+    let mut buffer = [0u8; 48];
+    file.read_exact(&mut buffer).context("Model file is too small")?;
+
+    let field = |i: usize| i32::from_le_bytes(buffer[i * 4..i * 4 + 4].try_into().unwrap());
+    let magic = u32::from_le_bytes(buffer[0..4].try_into().unwrap());
+    if magic != GGML_MAGIC {
+        if buffer.starts_with(b"<") {
+            return Err(anyhow!("Not a GGML model (looks like an HTML page; the download probably failed)"));
+        }
+        return Err(anyhow!("Not a GGML model (bad magic {:#010x})", magic));
+    }
+
+    Ok(GgmlHeader {
+        n_vocab: field(1),
+        n_audio_layer: field(5),
+        n_text_layer: field(9),
+        n_mels: field(10),
+        ftype: field(11),
+    })
+}
+
+/// 校验模型文件头，f16 模型再按参考大小检查是否下载不完整
+pub fn check_model_file(path: &Path) -> Check {
+    let redownload = format!(
+        "重新下载: curl -L -o {} https://huggingface.co/ggerganov/whisper.cpp/resolve/main/{}",
+        path.display(),
+        path.file_name().unwrap_or_default().to_string_lossy()
+    );
+    let size = fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+    let header = match read_ggml_header(path) {
+        Ok(header) => header,
+        Err(e) => return Check::fail("模型文件", format!("{}: {:#}", path.display(), e), redownload),
+    };
+
+    let size_text = format!("{:.2} GiB", size as f64 / (1u64 << 30) as f64);
+    let Some((model_type, expected)) = header.model_type() else {
+        return Check::ok(
+            "模型文件",
+            format!("{} (GGML, {}, {})", path.display(), header.ftype_name(), size_text),
+        );
+    };
+    if header.ftype == 1 && size < expected / 100 * 95 {
+        return Check::fail(
+            "模型文件",
+            format!(
+                "{} 只有 {}，{} f16 模型应约为 {:.2} GiB，可能下载不完整",
+                path.display(),
+                size_text,
+                model_type,
+                expected as f64 / (1u64 << 30) as f64
+            ),
+            redownload,
+        );
+    }
+    Check::ok(
+        "模型文件",
+        format!("{} ({}, {}, {})", path.display(), model_type, header.ftype_name(), size_text),
+    )
+}
+
+async fn check_translator(client: &Client, config: &TranslatorConfig) -> Check {
+    let name = "翻译服务";
+    let alternatives = "只在 --translate 时需要；也可以改用 --translator libre 或 --translator dict";
+    match config.provider {
+        Provider::Youdao => {
+            let url = config.base_url.clone().unwrap_or_else(|| YOUDAO_BASE_URL.to_string());
+            match client.get(&url).send().await {
+                Ok(response) => Check::ok(name, format!("youdao {} (HTTP {})", url, response.status().as_u16())),
+                Err(e) => Check::warn(
+                    name,
+                    format!("无法连接 youdao {}: {}", url, e),
+                    format!("检查网络或代理设置（HTTPS_PROXY）\n{}", alternatives),
+                ),
+            }
+        }
+        Provider::Libre => {
+            let Some(url) = &config.base_url else {
+                return Check::warn(
+                    name,
+                    "libre 未设置服务地址",
+                    "使用 --translator-url 或 video2en.toml 的 [translation] url 指定 LibreTranslate 地址",
+                );
+            };
+            let languages_url = format!("{}/languages", url.trim_end_matches('/'));
+            let start_hint = "启动 LibreTranslate: docker run -d -p 5050:5000 libretranslate/libretranslate\n\
+                              然后使用 --translator-url http://localhost:5050";
+            match client.get(&languages_url).send().await {
+                Ok(response) if response.status().is_success() => Check::ok(name, format!("libre {}", url)),
+                Ok(response) => Check::warn(
+                    name,
+                    format!("libre {} 返回 HTTP {}", languages_url, response.status().as_u16()),
+                    start_hint,
+                ),
+                Err(e) => Check::warn(name, format!("无法连接 libre {}: {}", url, e), start_hint),
+            }
+        }
+        Provider::Dict => {
+            let Some(path) = &config.dict_file else {
+                return Check::warn(name, "dict 未设置词典文件", "使用 --dict-file 指定词典文件（每行: 英文<TAB>翻译）");
+            };
+            match DictTranslator::load(path) {
+                Ok(_) => Check::ok(name, format!("dict {}", path.display())),
+                Err(e) => Check::warn(name, format!("{:#}", e), "检查 --dict-file 路径（每行: 英文<TAB>翻译）"),
+            }
+        }
+    }
+}
+
+async fn check_tts(client: &Client, url: &str) -> Check {
+    let hint = format!(
+        "只在 txt2audio 中需要：启动 TTS 服务（txt2audio 调用 POST {}/speak），\n\
+         或用 --tts-url / video2en.toml 的 [tts] url 修改地址",
+        url.trim_end_matches('/')
+    );
+    match client.get(url).send().await {
+        Ok(response) => Check::ok("TTS 服务", format!("{} (HTTP {})", url, response.status().as_u16())),
+        Err(e) => Check::warn("TTS 服务", format!("无法连接 {}: {}", url, e), hint),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn write_model(path: &Path, audio_layers: i32, text_layers: i32, ftype: i32, size: usize) {
+        let mut bytes = GGML_MAGIC.to_le_bytes().to_vec();
+        for value in [51865, 1500, 512, 8, audio_layers, 448, 512, 8, text_layers, 80, ftype] {
+            bytes.extend(i32::to_le_bytes(value));
+        }
+        bytes.resize(size, 0);
+        fs::write(path, bytes).unwrap();
+    }
+
+    #[test]
+    fn test_model_file_check() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ggml-base.bin");
+
+        write_model(&path, 6, 6, 1, 147_951_465);
+        let header = read_ggml_header(&path).unwrap();
+        assert_eq!(header.model_type().map(|(name, _)| name), Some("base"));
+        assert_eq!(check_model_file(&path).status, CheckStatus::Ok);
+
+        // 下载中断的 f16 模型
+        write_model(&path, 6, 6, 1, 1024);
+        assert_eq!(check_model_file(&path).status, CheckStatus::Fail);
+
+        // 量化模型不按 f16 大小检查
+        write_model(&path, 6, 6, 2008, 1024);
+        assert_eq!(check_model_file(&path).status, CheckStatus::Ok);
+
+        // 下载到的是网页而不是模型
+        fs::write(&path, "<!DOCTYPE html><html><body>Not Found</body></html>").unwrap();
+        let check = check_model_file(&path);
+        assert_eq!(check.status, CheckStatus::Fail);
+        assert!(check.detail.contains("HTML"));
+    }
+
+    #[test]
+    fn test_model_resolution() {
+        let dir = tempfile::tempdir().unwrap();
+        let options = DoctorOptions {
+            workspace: dir.path().to_path_buf(),
+            dirs: WorkspaceDirs::default(),
+            backend: Backend::WhisperCli,
+            whisper_cli: "whisper-cli".to_string(),
+            model_name: "ggml-large.bin".to_string(),
+            translator: TranslatorConfig::default(),
+            tts_url: String::new(),
+        };
+        let models_dir = dir.path().join("models");
+        fs::create_dir_all(&models_dir).unwrap();
+        assert_eq!(check_model(&options).status, CheckStatus::Fail);
+
+        // 和处理时一样，指定的模型不存在时使用唯一的模型
+        write_model(&models_dir.join("ggml-base.bin"), 6, 6, 2008, 1024);
+        let check = check_model(&options);
+        assert_eq!(check.status, CheckStatus::Ok);
+        assert!(check.detail.contains("ggml-base.bin"), "{}", check.detail);
+
+        write_model(&models_dir.join("ggml-small.bin"), 12, 12, 2008, 1024);
+        assert_eq!(check_model(&options).status, CheckStatus::Fail);
+    }
+
+    #[test]
+    fn test_config_check() {
+        let dir = tempfile::tempdir().unwrap();
+        let (check, config) = check_config(dir.path());
+        assert_eq!(check.status, CheckStatus::Ok);
+        assert_eq!(config, WorkspaceConfig::default());
+
+        // 配置文件有错误时报告为失败项，其余检查使用默认配置
+        fs::write(WorkspaceConfig::path(dir.path()), "[video2en]\nmodel = 1\n").unwrap();
+        let (check, config) = check_config(dir.path());
+        assert_eq!(check.status, CheckStatus::Fail);
+        assert!(check.detail.contains("video2en.toml"), "{}", check.detail);
+        assert_eq!(config, WorkspaceConfig::default());
+    }
+}
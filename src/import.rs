@@ -0,0 +1,207 @@
+use anyhow::{anyhow, Context, Result};
+use lingua::Language;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use crate::segment::{parse_ass, parse_srt, parse_vtt, Segment};
+
+/// 可导入的外挂字幕扩展名
+pub const SUBTITLE_EXTENSIONS: &[&str] = &["srt", "vtt", "ass", "ssa"];
+
+/// ffmpeg 能转换为 SRT 的内嵌文本字幕编码（图形字幕如 PGS/VobSub 无法导入）
+const TEXT_SUBTITLE_CODECS: &[&str] = &["subrip", "srt", "ass", "ssa", "webvtt", "mov_text", "text"];
+
+/// 已有字幕的处理方式
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExistingSubtitles {
+    /// 有外挂或内嵌字幕时直接使用，跳过语音识别
+    Prefer,
+    /// 忽略已有字幕，总是运行语音识别
+    Ignore,
+}
+
+/// 导入得到的字幕
+pub struct ImportedSubtitles {
+    /// 字幕来源：外挂字幕文件或从视频中提取出的 SRT
+    pub path: PathBuf,
+    pub segments: Vec<Segment>,
+}
+
+/// 按外挂字幕 → 内嵌字幕的顺序查找输入文件已有的目标语言字幕
+pub fn import_subtitles(input_file: &Path, output_dir: &Path, target: Language) -> Result<Option<ImportedSubtitles>> {
+    if let Some(path) = find_sidecar(input_file, target)? {
+        let segments = read_subtitle_file(&path)?;
+        if !segments.is_empty() {
+            return Ok(Some(ImportedSubtitles { path, segments }));
+        }
+    }
+
+    if let Some(path) = extract_embedded_subtitles(input_file, output_dir, target)? {
+        let segments = read_subtitle_file(&path)?;
+        if !segments.is_empty() {
+            return Ok(Some(ImportedSubtitles { path, segments }));
+        }
+    }
+
+    Ok(None)
+}
+
+/// 查找与输入文件同名的外挂字幕：带目标语言标记的 `<name>.en.srt` 优先，其次是不带语言标记的 `<name>.srt`；
+/// 其他语言的字幕不使用
+pub fn find_sidecar(input_file: &Path, target: Language) -> Result<Option<PathBuf>> {
+    let (Some(dir), Some(stem)) = (input_file.parent(), input_file.file_stem()) else {
+        return Ok(None);
+    };
+    let stem = stem.to_string_lossy();
+
+    let mut candidates = Vec::new();
+    for entry in fs::read_dir(dir).context(format!("Failed to read directory: {}", dir.display()))? {
+        let path = entry.context("Failed to read directory entry")?.path();
+        let Some(extension) = path.extension().map(|ext| ext.to_string_lossy().to_lowercase()) else {
+            continue;
+        };
+        if !path.is_file() || !SUBTITLE_EXTENSIONS.contains(&extension.as_str()) {
+            continue;
+        }
+
+        let file_stem = path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+        let language = match file_stem.strip_prefix(stem.as_ref()) {
+            Some("") => "",
+            Some(rest) => match rest.strip_prefix('.') {
+                Some(language) if !language.contains('.') => language,
+                _ => continue,
+            },
+            None => continue,
+        };
+
+        let rank = if is_language_tag(language, target) {
+            0
+        } else if language.is_empty() {
+            1
+        } else {
+            continue;
+        };
+        candidates.push((rank, path));
+    }
+
+    candidates.sort();
+    Ok(candidates.into_iter().next().map(|(_, path)| path))
+}
+
+/// 语言标记是否表示目标语言：ISO 639-1（`en`）、ISO 639-3（`eng`）或英文名称（`english`）
+fn is_language_tag(tag: &str, target: Language) -> bool {
+    !tag.is_empty()
+        && [
+            target.iso_code_639_1().to_string(),
+            target.iso_code_639_3().to_string(),
+            target.to_string(),
+        ]
+        .iter()
+        .any(|code| code.eq_ignore_ascii_case(tag))
+}
+
+/// 按扩展名解析 SRT/VTT/ASS 字幕文件
+pub fn read_subtitle_file(path: &Path) -> Result<Vec<Segment>> {
+    let content = fs::read_to_string(path)
+        .context(format!("Failed to read subtitle file: {}", path.display()))?;
+    let extension = path.extension().unwrap_or_default().to_string_lossy().to_lowercase();
+
+    match extension.as_str() {
+        "srt" => parse_srt(&content),
+        "vtt" => parse_vtt(&content),
+        "ass" | "ssa" => parse_ass(&content),
+        _ => Err(anyhow!("Unsupported subtitle format: {}", path.display())),
+    }
+    .context(format!("Failed to parse subtitle file: {}", path.display()))
+}
+
+/// 用 ffprobe 查找内嵌的文本字幕流（目标语言优先，其次是没有语言标签的流），并用 ffmpeg 提取为 `<name>.embedded.srt`
+pub fn extract_embedded_subtitles(input_file: &Path, output_dir: &Path, target: Language) -> Result<Option<PathBuf>> {
+    if which::which("ffprobe").is_err() {
+        println!("⚠️ ffprobe not found in PATH, skipping embedded subtitle detection");
+        return Ok(None);
+    }
+
+    let output = Command::new("ffprobe")
+        .args([
+            "-v", "error",
+            "-select_streams", "s",
+            "-show_entries", "stream=index,codec_name:stream_tags=language",
+            "-of", "csv=p=0",
+        ])
+        .arg(input_file)
+        .output()
+        .context("Failed to execute ffprobe")?;
+
+    if !output.status.success() {
+        return Err(anyhow!(
+            "ffprobe failed for {}: {}",
+            input_file.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    // 每行形如 `2,subrip,eng`，语言标签可能缺失
+    let mut streams: Vec<(u32, String)> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.trim().split(',');
+            let index = fields.next()?.parse().ok()?;
+            let codec = fields.next()?;
+            let language = fields.next().unwrap_or_default().to_lowercase();
+            let wanted = language.is_empty() || is_language_tag(&language, target);
+            (TEXT_SUBTITLE_CODECS.contains(&codec) && wanted).then_some((index, language))
+        })
+        .collect();
+    streams.sort_by_key(|(_, language)| language.is_empty());
+
+    let Some((index, _)) = streams.into_iter().next() else {
+        return Ok(None);
+    };
+
+    let input_stem = input_file.file_stem().unwrap_or_default().to_string_lossy().to_string();
+    let subtitle_path = output_dir.join(format!("{}.embedded.srt", input_stem));
+    println!("🎞️ Extracting embedded subtitle stream #{} from: {}", index, input_file.display());
+
+    let status = Command::new("ffmpeg")
+        .arg("-y")
+        .arg("-i").arg(input_file)
+        .args(["-map", &format!("0:{}", index), "-f", "srt"])
+        .arg(&subtitle_path)
+        .status()
+        .context("Failed to execute ffmpeg")?;
+
+    if !status.success() {
+        return Err(anyhow!("ffmpeg failed to extract subtitles with exit code: {}", status));
+    }
+
+    Ok(Some(subtitle_path))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_find_sidecar() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("movie.mkv");
+        for name in ["movie.mkv", "movie.zh.srt", "movie.vtt", "movie.en.ass", "movie2.srt", "movie.en.txt"] {
+            fs::write(dir.path().join(name), "").unwrap();
+        }
+        assert_eq!(find_sidecar(&input, Language::English).unwrap(), Some(dir.path().join("movie.en.ass")));
+        assert_eq!(find_sidecar(&input, Language::Chinese).unwrap(), Some(dir.path().join("movie.zh.srt")));
+
+        fs::remove_file(dir.path().join("movie.en.ass")).unwrap();
+        assert_eq!(find_sidecar(&input, Language::English).unwrap(), Some(dir.path().join("movie.vtt")));
+
+        // 只有其他语言的字幕时不导入
+        fs::remove_file(dir.path().join("movie.vtt")).unwrap();
+        assert_eq!(find_sidecar(&input, Language::English).unwrap(), None);
+        fs::rename(dir.path().join("movie.zh.srt"), dir.path().join("movie.English.srt")).unwrap();
+        assert_eq!(find_sidecar(&input, Language::English).unwrap(), Some(dir.path().join("movie.English.srt")));
+    }
+}
@@ -0,0 +1,346 @@
+use anyhow::{anyhow, Result};
+use lingua::{Language, LanguageDetector, LanguageDetectorBuilder};
+use regex::Regex;
+use std::ops::Range;
+
+use crate::segment::Segment;
+
+/// 语言过滤配置
+#[derive(Debug, Clone)]
+pub struct LanguageOptions {
+    /// 要保留的目标语言
+    pub target: Language,
+    /// lingua 检测时的候选语言（会自动包含目标语言）
+    pub candidates: Vec<Language>,
+    /// 目标语言文字占比不低于该值时直接判定为目标语言（仅在候选语言中没有同文字语言时使用）
+    pub script_ratio: f64,
+    /// lingua 判定为目标语言所需的最低置信度
+    pub min_confidence: f64,
+}
+
+impl Default for LanguageOptions {
+    fn default() -> Self {
+        Self {
+            target: Language::English,
+            candidates: vec![Language::English, Language::Chinese],
+            script_ratio: 0.6,
+            min_confidence: 0.0,
+        }
+    }
+}
+
+/// 一段文本的语言检测结果
+#[derive(Debug, Clone, PartialEq)]
+pub struct Detection {
+    pub language: Option<Language>,
+    /// 检测出的语言的置信度（0~1）
+    pub confidence: Option<f64>,
+    pub is_target: bool,
+}
+
+/// 按目标语言过滤segment：先看文字占比，不能确定时用 lingua 检测
+pub struct LanguageFilter {
+    options: LanguageOptions,
+    detector: LanguageDetector,
+    /// 可以只凭文字占比判断时目标语言的文字（目标语言文字已知，且候选语言中没有同文字的语言）
+    script_shortcut: Option<Script>,
+}
+
+impl LanguageFilter {
+    pub fn new(mut options: LanguageOptions) -> Self {
+        if !options.candidates.contains(&options.target) {
+            options.candidates.push(options.target);
+        }
+
+        // lingua 至少需要两种语言
+        let mut languages = options.candidates.clone();
+        if languages.len() < 2 {
+            languages.push(if options.target == Language::English { Language::Chinese } else { Language::English });
+        }
+        let detector = LanguageDetectorBuilder::from_languages(&languages).build();
+
+        let script_shortcut = Script::of(options.target).filter(|&target_script| {
+            options
+                .candidates
+                .iter()
+                .all(|&language| language == options.target || Script::of(language) != Some(target_script))
+        });
+
+        Self {
+            options,
+            detector,
+            script_shortcut,
+        }
+    }
+
+    pub fn options(&self) -> &LanguageOptions {
+        &self.options
+    }
+
+    pub fn detect(&self, text: &str) -> Detection {
+        let cleaned = clean_text(text);
+        if cleaned.is_empty() {
+            return Detection {
+                language: None,
+                confidence: None,
+                is_target: false,
+            };
+        }
+
+        let language = self.detector.detect_language_of(&cleaned);
+        let confidence = language.map(|language| self.detector.compute_language_confidence(&cleaned, language));
+
+        // 方法1：目标语言文字占比
+        let script_match = self
+            .script_shortcut
+            .map(|script| script.matches(&cleaned) && script_ratio(&cleaned, script) >= self.options.script_ratio)
+            .unwrap_or(false);
+
+        // 方法2：lingua 检测结果和置信度
+        let detected_match = language == Some(self.options.target)
+            && confidence.unwrap_or(0.0) >= self.options.min_confidence;
+
+        Detection {
+            language,
+            confidence,
+            is_target: script_match || detected_match,
+        }
+    }
+
+    pub fn is_target(&self, text: &str) -> bool {
+        self.detect(text).is_target
+    }
+
+    /// 把混合语言的文本切分为语言一致的片段，返回每段的字节范围（已去掉首尾空白）及是否目标语言。
+    /// lingua 的多语言检测对短句容易整行判为一种语言，目标语言文字独特时再按文字边界细分
+    pub fn split_runs(&self, text: &str) -> Vec<(Range<usize>, bool)> {
+        let mut pieces: Vec<Range<usize>> = Vec::new();
+        for result in self.detector.detect_multiple_languages_of(text) {
+            let range = result.start_index()..result.end_index();
+            match self.script_shortcut {
+                Some(script) => pieces.extend(split_by_script(text, range, script)),
+                None => pieces.push(range),
+            }
+        }
+
+        // 相邻且判定结果相同的片段合并
+        let mut runs: Vec<(Range<usize>, bool)> = Vec::new();
+        for piece in pieces {
+            let piece = trim_range(text, piece);
+            if piece.is_empty() || clean_text(&text[piece.clone()]).is_empty() {
+                continue;
+            }
+            let is_target = self.is_target(&text[piece.clone()]);
+            match runs.last_mut() {
+                Some((last, last_target)) if *last_target == is_target => last.end = piece.end,
+                _ => runs.push((piece, is_target)),
+            }
+        }
+        runs
+    }
+
+    /// 同时含有目标语言和其他语言的segment拆分为多个子segment，时间按字符位置线性插值，
+    /// 子segment的 `context` 记录原始整行；无需拆分时原样返回
+    pub fn split_mixed(&self, segment: &Segment) -> Vec<Segment> {
+        let runs = self.split_runs(&segment.text);
+        let has_target = runs.iter().any(|(_, is_target)| *is_target);
+        if runs.len() < 2 || !has_target {
+            return vec![segment.clone()];
+        }
+
+        let text = &segment.text;
+        let total_chars = text.chars().count().max(1) as u64;
+        let duration = segment.end_ms.saturating_sub(segment.start_ms) as u64;
+        let time_at = |byte: usize| {
+            let chars = text[..byte].chars().count() as u64;
+            segment.start_ms + (duration * chars / total_chars) as u32
+        };
+
+        runs.into_iter()
+            .map(|(range, _)| {
+                let mut piece = Segment::new(time_at(range.start), time_at(range.end), &text[range]);
+                piece.context = Some(text.clone());
+                piece
+            })
+            .collect()
+    }
+}
+
+/// 在目标语言文字与其他文字的交界处切分；空格、标点、数字跟随前一段
+fn split_by_script(text: &str, range: Range<usize>, script: Script) -> Vec<Range<usize>> {
+    let mut pieces = Vec::new();
+    let mut start = range.start;
+    let mut current: Option<bool> = None;
+
+    for (offset, c) in text[range.clone()].char_indices() {
+        if !c.is_alphabetic() {
+            continue;
+        }
+        let in_script = script.contains(c);
+        match current {
+            Some(previous) if previous != in_script => {
+                let index = range.start + offset;
+                pieces.push(start..index);
+                start = index;
+                current = Some(in_script);
+            }
+            _ => current = Some(in_script),
+        }
+    }
+    pieces.push(start..range.end);
+    pieces
+}
+
+/// 去掉字节范围首尾的空白
+fn trim_range(text: &str, range: Range<usize>) -> Range<usize> {
+    let slice = &text[range.clone()];
+    let start = range.start + (slice.len() - slice.trim_start().len());
+    let end = range.end - (slice.len() - slice.trim_end().len());
+    start..end.max(start)
+}
+
+/// 按 ISO 639-1 代码（如 `en`、`ja`）或英文名称（如 `Spanish`）查找语言
+pub fn parse_language(value: &str) -> Result<Language> {
+    let value = value.trim();
+    Language::all()
+        .into_iter()
+        .find(|language| {
+            language.iso_code_639_1().to_string().eq_ignore_ascii_case(value)
+                || language.to_string().eq_ignore_ascii_case(value)
+        })
+        .ok_or_else(|| anyhow!("Unsupported language: {} (supported: {})", value, supported_languages().join(", ")))
+}
+
+/// 编译时启用的语言的 ISO 639-1 代码
+pub fn supported_languages() -> Vec<String> {
+    let mut codes: Vec<String> = Language::all()
+        .into_iter()
+        .map(|language| language.iso_code_639_1().to_string())
+        .collect();
+    codes.sort();
+    codes
+}
+
+/// 书写文字，用于文字占比判断
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Script {
+    Latin,
+    Han,
+    /// 日文：假名和汉字
+    Japanese,
+    Hangul,
+    Cyrillic,
+}
+
+impl Script {
+    /// 语言的书写文字；没有列出的语言返回 `None`，不使用文字占比判断。
+    /// 目前启用的 lingua 语言都已列出，兜底分支留给以后新增的语言
+    #[allow(unreachable_patterns)]
+    fn of(language: Language) -> Option<Script> {
+        match language {
+            Language::English
+            | Language::Spanish
+            | Language::French
+            | Language::German
+            | Language::Italian
+            | Language::Portuguese => Some(Script::Latin),
+            Language::Chinese => Some(Script::Han),
+            Language::Japanese => Some(Script::Japanese),
+            Language::Korean => Some(Script::Hangul),
+            Language::Russian => Some(Script::Cyrillic),
+            _ => None,
+        }
+    }
+
+    /// 文本是否带有该文字的特征字符：日文与中文共用汉字，必须含有假名才算日文
+    fn matches(&self, text: &str) -> bool {
+        match self {
+            Script::Japanese => text.chars().any(is_kana),
+            _ => true,
+        }
+    }
+
+    fn contains(&self, c: char) -> bool {
+        let han = matches!(c, '\u{4E00}'..='\u{9FFF}' | '\u{3400}'..='\u{4DBF}');
+        match self {
+            Script::Latin => c.is_ascii_alphabetic() || matches!(c, '\u{00C0}'..='\u{024F}'),
+            Script::Han => han,
+            Script::Japanese => han || is_kana(c),
+            Script::Hangul => matches!(c, '\u{AC00}'..='\u{D7AF}' | '\u{1100}'..='\u{11FF}'),
+            Script::Cyrillic => matches!(c, '\u{0400}'..='\u{04FF}'),
+        }
+    }
+}
+
+/// 平假名和片假名
+fn is_kana(c: char) -> bool {
+    matches!(c, '\u{3040}'..='\u{30FF}')
+}
+
+/// 文本中属于指定文字的字符比例
+fn script_ratio(text: &str, script: Script) -> f64 {
+    let total = text.chars().count();
+    if total == 0 {
+        return 0.0;
+    }
+    text.chars().filter(|&c| script.contains(c)).count() as f64 / total as f64
+}
+
+/// 去掉标点等非文字符号并合并空白
+pub fn clean_text(text: &str) -> String {
+    let re = Regex::new(r"[^\p{L}\p{N}\s]").unwrap();
+    re.replace_all(text, " ")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_language_filter() {
+        assert_eq!(parse_language("ES").unwrap(), Language::Spanish);
+        assert_eq!(parse_language("japanese").unwrap(), Language::Japanese);
+        assert!(parse_language("xx").is_err());
+
+        // 英文和西班牙文同为拉丁字母，不能只看字母占比
+        let filter = LanguageFilter::new(LanguageOptions {
+            candidates: vec![Language::English, Language::Spanish],
+            ..Default::default()
+        });
+        assert!(filter.is_target("I don't know what you are talking about."));
+        assert!(!filter.is_target("No sé de qué estás hablando, amigo."));
+
+        let filter = LanguageFilter::new(LanguageOptions {
+            target: Language::Japanese,
+            candidates: vec![Language::English, Language::Chinese],
+            ..Default::default()
+        });
+        let detection = filter.detect("ありがとうございます");
+        assert!(detection.is_target);
+        assert_eq!(detection.language, Some(Language::Japanese));
+        assert!(!filter.is_target("Thank you very much"));
+        // 只有汉字、没有假名的中文不算日文
+        assert!(!filter.is_target("我们明天早上九点在学校门口见面"));
+
+        // 置信度过低时不算目标语言
+        let filter = LanguageFilter::new(LanguageOptions {
+            min_confidence: 1.01,
+            script_ratio: 1.01,
+            ..Default::default()
+        });
+        assert!(!filter.is_target("Take it easy"));
+
+        // 混合语言的整行拆为语言一致的子segment
+        let filter = LanguageFilter::new(LanguageOptions::default());
+        let pieces = filter.split_mixed(&Segment::new(1000, 3000, "这个叫 take it easy 就是放轻松"));
+        let texts: Vec<&str> = pieces.iter().map(|s| s.text.as_str()).collect();
+        assert_eq!(texts, vec!["这个叫", "take it easy", "就是放轻松"]);
+        // 共 22 个字符，英文在第 4~16 个字符之间
+        assert_eq!((pieces[1].start_ms, pieces[1].end_ms), (1363, 2454));
+        assert_eq!(pieces[1].context.as_deref(), Some("这个叫 take it easy 就是放轻松"));
+        assert_eq!(filter.split_mixed(&Segment::new(0, 1000, "See you tomorrow!")).len(), 1);
+    }
+}
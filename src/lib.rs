@@ -1,2 +1,25 @@
 pub mod youdao;
 pub mod youdao_translate;
+pub mod anki;
+pub mod audio;
+pub mod cache;
+pub mod clip;
+pub mod dedup;
+pub mod doctor;
+pub mod import;
+pub mod language;
+pub mod segment;
+pub mod sentence_index;
+pub mod snapshot;
+pub mod state;
+pub mod throttle;
+pub mod toolchain;
+pub mod transcriber;
+pub mod transcript;
+pub mod parallel;
+pub mod pipeline;
+pub mod translator;
+pub mod vad;
+pub mod subtitle;
+pub mod text;
+pub mod workspace;
@@ -0,0 +1,255 @@
+use anyhow::{anyhow, Context, Result};
+use std::{
+    path::Path,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread,
+};
+
+use crate::audio::{read_wav_samples, write_wav_samples, WHISPER_SAMPLE_RATE};
+use crate::text::normalize_text;
+use crate::segment::Segment;
+use crate::transcriber::Transcriber;
+use crate::vad::{chunk_samples, offset_segments, AudioChunk};
+
+/// 分块并行识别参数
+#[derive(Debug, Clone)]
+pub struct ParallelOptions {
+    /// 同时运行的识别任务数，识别线程总数在各任务间平均分配
+    pub workers: usize,
+    /// 每块时长
+    pub chunk_ms: u32,
+    /// 相邻两块重叠的时长，避免在句子中间切断
+    pub overlap_ms: u32,
+}
+
+impl Default for ParallelOptions {
+    fn default() -> Self {
+        Self {
+            workers: 2,
+            chunk_ms: 5 * 60 * 1000,
+            overlap_ms: 5000,
+        }
+    }
+}
+
+impl ParallelOptions {
+    /// 每个任务分到的识别线程数
+    pub fn threads_per_worker(&self, total_threads: usize) -> usize {
+        (total_threads / self.workers.max(1)).max(1)
+    }
+}
+
+/// 把音频切成相互重叠的块，由多个任务并行识别，再合并为一条时间轴
+pub struct ParallelTranscriber {
+    inner: Box<dyn Transcriber>,
+    options: ParallelOptions,
+}
+
+impl ParallelTranscriber {
+    /// `inner` 应按 [`ParallelOptions::threads_per_worker`] 配置线程数
+    pub fn new(inner: Box<dyn Transcriber>, options: ParallelOptions) -> Self {
+        Self { inner, options }
+    }
+}
+
+impl Transcriber for ParallelTranscriber {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn transcribe(&self, audio_path: &Path, output_dir: &Path) -> Result<Vec<Segment>> {
+        let samples = read_wav_samples(audio_path)?;
+        let total_ms = (samples.len() as u64 * 1000 / WHISPER_SAMPLE_RATE as u64).min(u32::MAX as u64) as u32;
+        let chunks = plan_chunks(total_ms, &self.options);
+        if chunks.len() < 2 {
+            return self.inner.transcribe(audio_path, output_dir);
+        }
+
+        let workers = self.options.workers.clamp(1, chunks.len());
+        println!("🧵 Transcribing {} chunks with {} workers", chunks.len(), workers);
+
+        // 分块音频放在临时目录中，识别完成后自动删除
+        let stem = audio_path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+        let chunk_dir = tempfile::Builder::new()
+            .prefix(&format!("{}.chunks.", stem))
+            .tempdir_in(output_dir)
+            .context("Failed to create chunk directory")?;
+        let mut chunk_paths = Vec::new();
+        for (i, chunk) in chunks.iter().enumerate() {
+            let chunk_path = chunk_dir.path().join(format!("{}_{:04}.wav", stem, i + 1));
+            write_wav_samples(&chunk_path, chunk_samples(&samples, chunk), WHISPER_SAMPLE_RATE)?;
+            chunk_paths.push(chunk_path);
+        }
+
+        // 各任务依次领取下一个未识别的块
+        let next = AtomicUsize::new(0);
+        let results: Mutex<Vec<Option<Result<Vec<Segment>>>>> = Mutex::new((0..chunks.len()).map(|_| None).collect());
+        thread::scope(|scope| {
+            for _ in 0..workers {
+                scope.spawn(|| loop {
+                    let i = next.fetch_add(1, Ordering::SeqCst);
+                    if i >= chunks.len() {
+                        break;
+                    }
+                    let result = self
+                        .inner
+                        .transcribe(&chunk_paths[i], chunk_dir.path())
+                        .context(format!("Failed to transcribe chunk {} of {}", i + 1, audio_path.display()));
+                    println!("✅ Chunk {}/{} done", i + 1, chunks.len());
+                    results.lock().unwrap()[i] = Some(result);
+                });
+            }
+        });
+
+        let mut transcribed = Vec::new();
+        for (chunk, result) in chunks.iter().zip(results.into_inner().unwrap()) {
+            let segments = result.ok_or_else(|| anyhow!("Chunk was not transcribed"))??;
+            transcribed.push((*chunk, offset_segments(segments, chunk)));
+        }
+        Ok(merge_chunks(transcribed))
+    }
+}
+
+/// 按块长和重叠时长切分 `[0, total_ms)`，最后一块不足时延伸到结尾
+pub fn plan_chunks(total_ms: u32, options: &ParallelOptions) -> Vec<AudioChunk> {
+    let step = options.chunk_ms.saturating_sub(options.overlap_ms).max(1);
+    let mut chunks = Vec::new();
+    let mut start_ms: u32 = 0;
+    loop {
+        // 很长的块长和重叠时长在接近 u32 上限时饱和，不会溢出
+        let end_ms = start_ms.saturating_add(options.chunk_ms);
+        // 剩余部分不足一个重叠区时并入当前块
+        if end_ms.saturating_add(options.overlap_ms) >= total_ms {
+            chunks.push(AudioChunk { start_ms, end_ms: total_ms });
+            return chunks;
+        }
+        chunks.push(AudioChunk { start_ms, end_ms });
+        start_ms += step;
+    }
+}
+
+/// 合并已换算为全局时间的各块识别结果：重叠区以中点为界，两块各取一半；
+/// 跨越分界、两块都识别出的同一句只保留一次
+pub fn merge_chunks(chunks: Vec<(AudioChunk, Vec<Segment>)>) -> Vec<Segment> {
+    let bounds: Vec<AudioChunk> = chunks.iter().map(|(chunk, _)| *chunk).collect();
+    let mut merged: Vec<Segment> = Vec::new();
+
+    for (i, (_, segments)) in chunks.into_iter().enumerate() {
+        // 与前后块重叠区的中点
+        let from = i.checked_sub(1).map(|p| midpoint(bounds[i].start_ms, bounds[p].end_ms)).unwrap_or(0);
+        let to = bounds.get(i + 1).map(|n| midpoint(n.start_ms, bounds[i].end_ms)).unwrap_or(u32::MAX);
+
+        for segment in segments {
+            let midpoint = segment.start_ms + segment.end_ms.saturating_sub(segment.start_ms) / 2;
+            if midpoint < from || midpoint >= to {
+                continue;
+            }
+            let duplicate = merged.iter().rev().take(3).any(|kept| {
+                kept.end_ms > segment.start_ms && normalize_text(&kept.text) == normalize_text(&segment.text)
+            });
+            if !duplicate {
+                merged.push(segment);
+            }
+        }
+    }
+
+    merged.sort_by_key(|segment| (segment.start_ms, segment.end_ms));
+    merged
+}
+
+/// 两个时间点的中点，按 u64 计算避免相加溢出
+fn midpoint(a: u32, b: u32) -> u32 {
+    ((a as u64 + b as u64) / 2) as u32
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_chunk_plan_and_merge() {
+        let options = ParallelOptions {
+            workers: 4,
+            chunk_ms: 10_000,
+            overlap_ms: 2000,
+        };
+        assert_eq!(options.threads_per_worker(8), 2);
+        assert_eq!(options.threads_per_worker(2), 1);
+
+        let chunks = plan_chunks(25_000, &options);
+        assert_eq!(
+            chunks,
+            vec![
+                AudioChunk { start_ms: 0, end_ms: 10_000 },
+                AudioChunk { start_ms: 8000, end_ms: 18_000 },
+                AudioChunk { start_ms: 16_000, end_ms: 25_000 },
+            ]
+        );
+        assert_eq!(plan_chunks(11_000, &options), vec![AudioChunk { start_ms: 0, end_ms: 11_000 }]);
+
+        // 第一块和第二块的重叠区 8~10s 中点为 9s
+        let merged = merge_chunks(vec![
+            (
+                chunks[0],
+                vec![
+                    Segment::new(1000, 3000, "First line."),
+                    Segment::new(8200, 9600, "Across the boundary"),
+                    Segment::new(9700, 10_000, "cut o"),
+                ],
+            ),
+            (
+                chunks[1],
+                vec![
+                    Segment::new(8000, 8500, "line"),
+                    Segment::new(8300, 9500, "across the boundary!"),
+                    Segment::new(9700, 11_000, "Cut off here."),
+                    Segment::new(16_500, 17_400, "Tail"),
+                ],
+            ),
+            (chunks[2], vec![Segment::new(16_900, 17_600, "tail"), Segment::new(20_000, 21_000, "Last.")]),
+        ]);
+        // 分界两侧重复识别出的 "Tail" / "tail" 只保留一次
+        let texts: Vec<&str> = merged.iter().map(|s| s.text.as_str()).collect();
+        assert_eq!(texts, vec!["First line.", "Across the boundary", "Cut off here.", "Tail", "Last."]);
+    }
+
+    #[test]
+    fn test_chunk_plan_near_u32_max() {
+        let options = ParallelOptions {
+            workers: 2,
+            chunk_ms: 3_000_000_000,
+            overlap_ms: 1_000_000_000,
+        };
+        let chunks = plan_chunks(u32::MAX, &options);
+        assert_eq!(
+            chunks,
+            vec![
+                AudioChunk { start_ms: 0, end_ms: 3_000_000_000 },
+                AudioChunk { start_ms: 2_000_000_000, end_ms: u32::MAX },
+            ]
+        );
+        let huge = ParallelOptions {
+            chunk_ms: u32::MAX,
+            overlap_ms: u32::MAX,
+            ..options
+        };
+        assert_eq!(plan_chunks(u32::MAX, &huge), vec![AudioChunk { start_ms: 0, end_ms: u32::MAX }]);
+
+        // 重叠区 2e9~3e9 的中点为 2.5e9
+        let merged = merge_chunks(vec![
+            (chunks[0], vec![Segment::new(2_400_000_000, 2_400_001_000, "Before.")]),
+            (
+                chunks[1],
+                vec![
+                    Segment::new(2_400_000_000, 2_400_001_000, "before"),
+                    Segment::new(4_000_000_000, 4_000_001_000, "After."),
+                ],
+            ),
+        ]);
+        let texts: Vec<&str> = merged.iter().map(|s| s.text.as_str()).collect();
+        assert_eq!(texts, vec!["Before.", "After."]);
+    }
+}
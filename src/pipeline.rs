@@ -0,0 +1,1920 @@
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+    sync::Arc,
+};
+use tokio::{sync::Semaphore, task::JoinSet};
+
+use crate::anki::{AnkiAudio, AnkiDeck, AnkiMedia, AnkiNote, AnkiOptions};
+use crate::audio::AudioData;
+use crate::clip::{clip_paths, extract_clips, ClipManifest, ClipOptions};
+use crate::dedup::{mark_duplicates, DedupOptions};
+use crate::import::{import_subtitles, ExistingSubtitles, ImportedSubtitles};
+use crate::language::{LanguageFilter, LanguageOptions};
+use crate::segment::{format_timestamp, Segment};
+use crate::sentence_index::SentenceIndex;
+use crate::snapshot::{extract_snapshots, has_video, snapshot_dir, SnapshotOptions};
+use crate::state::{hash_file, hash_text, Stage, StateManifest};
+use crate::subtitle::{Cue, CueLine, SubtitleFormat};
+use crate::text::{normalize_text, tsv_field};
+pub use crate::toolchain::Backend;
+use crate::toolchain::{ffmpeg_install_hint, resolve_model_file};
+pub use crate::transcript::Transcript;
+use crate::transcriber::{TranscribeOptions, Transcriber, WhisperCliTranscriber};
+use crate::cache::{CacheMode, TranslationCache};
+use crate::throttle::TokenBucket;
+use crate::parallel::{ParallelOptions, ParallelTranscriber};
+use crate::vad::{VadOptions, VadTranscriber};
+use crate::translator::{create_translator, Translation, Translator, TranslatorConfig};
+use crate::workspace::{check_output_folders, WorkspaceDirs};
+
+/// 支持的视频/音频扩展名
+pub const MEDIA_EXTENSIONS: &[&str] = &[
+    "mp4", "avi", "mkv", "mov", "wmv", "flv", "webm", "mp3", "wav", "flac", "aac", "ogg", "m4a",
+];
+
+/// 每个输入文件可生成的输出类型
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputKind {
+    /// <name>.all.<格式>: 全量字幕（包含中英文）
+    #[value(alias = "all-srt")]
+    AllSubs,
+    /// <name>.en.<格式>: 仅英文字幕
+    #[value(alias = "en-srt")]
+    EnSubs,
+    /// <name>.en.txt: 仅英文纯文本
+    EnTxt,
+    /// <name>.unique.txt: 去重后的英文纯文本
+    UniqueTxt,
+    /// <name>.bilingual.txt: 中英对照纯文本（需要 --translate）
+    BilingualTxt,
+    /// <name>.bilingual.<格式>: 中英双语字幕（需要 --translate）
+    #[value(alias = "bilingual-srt", alias = "bilingual-vtt")]
+    BilingualSubs,
+    /// <name>.bilingual.tsv: 时间、英文、中文制表符分隔表格（需要 --translate）
+    BilingualTsv,
+    /// <name>.json: 带每段元数据的结构化转写结果
+    Json,
+    /// <name>.jsonl: 头部行加每行一个segment的 JSON-lines 转写结果
+    Jsonl,
+}
+
+/// 可以用 `--force` 单独重新生成的产物
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Artifact {
+    /// 提取的 16kHz WAV（重新提取后之后的阶段全部重新执行）
+    Audio,
+    /// 识别结果检查点 `<name>.raw.json`（重新识别）
+    Transcript,
+    /// 全量和英文字幕
+    Subtitles,
+    /// 英文纯文本和去重文本
+    Text,
+    /// JSON / JSON-lines 转写结果
+    Json,
+    /// 双语文本、字幕和表格
+    Bilingual,
+    /// 句子音频片段
+    Clips,
+    /// 视频截图
+    Snapshots,
+    /// Anki 牌组
+    Anki,
+}
+
+impl Artifact {
+    /// 输出文件类产物，不带参数的 `--force` 覆盖这些产物
+    pub const OUTPUTS: &'static [Artifact] = &[
+        Artifact::Subtitles,
+        Artifact::Text,
+        Artifact::Json,
+        Artifact::Bilingual,
+        Artifact::Clips,
+        Artifact::Snapshots,
+        Artifact::Anki,
+    ];
+}
+
+/// 双语输出的排版方式
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BilingualLayout {
+    /// 英文在上，中文在下
+    EnOverZh,
+    /// 中文在上，英文在下
+    ZhOverEn,
+    /// 英文和中文分别输出为独立的字幕轨/段落
+    Separate,
+}
+
+impl OutputKind {
+    pub const ALL: &'static [OutputKind] = &[
+        OutputKind::AllSubs,
+        OutputKind::EnSubs,
+        OutputKind::EnTxt,
+        OutputKind::UniqueTxt,
+        OutputKind::BilingualTxt,
+        OutputKind::BilingualSubs,
+        OutputKind::BilingualTsv,
+        OutputKind::Json,
+        OutputKind::Jsonl,
+    ];
+
+    /// 输出文件所属的产物类别
+    pub fn artifact(&self) -> Artifact {
+        match self {
+            OutputKind::AllSubs | OutputKind::EnSubs => Artifact::Subtitles,
+            OutputKind::EnTxt | OutputKind::UniqueTxt => Artifact::Text,
+            OutputKind::BilingualTxt | OutputKind::BilingualSubs | OutputKind::BilingualTsv => Artifact::Bilingual,
+            OutputKind::Json | OutputKind::Jsonl => Artifact::Json,
+        }
+    }
+
+    /// 文件名后缀；字幕类输出还会按 `subtitle_formats` 追加格式扩展名
+    pub fn suffix(&self) -> &'static str {
+        match self {
+            OutputKind::AllSubs => "all",
+            OutputKind::EnSubs => "en",
+            OutputKind::EnTxt => "en.txt",
+            OutputKind::UniqueTxt => "unique.txt",
+            OutputKind::BilingualTxt => "bilingual.txt",
+            OutputKind::BilingualSubs => "bilingual",
+            OutputKind::BilingualTsv => "bilingual.tsv",
+            OutputKind::Json => "json",
+            OutputKind::Jsonl => "jsonl",
+        }
+    }
+}
+
+/// 流水线配置，通过 [`PipelineConfig::builder`] 创建
+#[derive(Debug, Clone)]
+pub struct PipelineConfig {
+    pub workspace: PathBuf,
+    /// workspace 中的子目录，相对路径相对于 workspace
+    pub dirs: WorkspaceDirs,
+    pub backend: Backend,
+    pub whisper_cli: String,
+    pub model_name: Option<String>,
+    pub transcribe: TranscribeOptions,
+    /// 强制重新生成的产物，其余已存在的产物跳过
+    pub force: Vec<Artifact>,
+    /// 运行前把非空的输出目录整体重命名为 `<目录名>_backup[_N]`（旧版行为）
+    pub archive_previous: bool,
+    /// 忽略状态文件中已完成的阶段，所有阶段重新执行
+    pub restart: bool,
+    pub translate: bool,
+    pub translator: TranslatorConfig,
+    pub outputs: Vec<OutputKind>,
+    pub bilingual_layout: BilingualLayout,
+    /// 把音频切成重叠的块并行识别，`transcribe.threads` 为所有任务共用的线程总数
+    pub parallel: Option<ParallelOptions>,
+    /// 识别前先做语音活动检测，只识别语音段（跳过长时间静音）
+    pub vad: Option<VadOptions>,
+    /// 目标语言及语言检测参数
+    pub language: LanguageOptions,
+    /// 把混合语言的segment拆分为语言一致的子segment
+    pub split_mixed: bool,
+    /// 跨文件、跨运行的全局句子去重：之前其他文件出现过的句子标记为已知并不再输出
+    pub global_dedup: bool,
+    /// 判断重复句子的方法和阈值
+    pub dedup: DedupOptions,
+    /// 为每个去重后的英文segment切出音频片段，`None` 为不切割
+    pub clips: Option<ClipOptions>,
+    /// 为每个去重后的英文segment截取视频画面，`None` 为不截图
+    pub snapshots: Option<SnapshotOptions>,
+    /// 导出 Anki 牌组，`None` 为不导出
+    pub anki: Option<AnkiOptions>,
+    /// 输入文件已有外挂/内嵌字幕时是否跳过语音识别
+    pub existing_subtitles: ExistingSubtitles,
+    /// 字幕类输出要写出的格式
+    pub subtitle_formats: Vec<SubtitleFormat>,
+    /// 复用输出目录中已有的 <name>.json/.jsonl 转写结果，跳过抽音和识别
+    pub from_transcripts: bool,
+}
+
+impl PipelineConfig {
+    pub fn builder(workspace: impl Into<PathBuf>) -> PipelineConfigBuilder {
+        PipelineConfigBuilder {
+            config: PipelineConfig {
+                workspace: workspace.into(),
+                dirs: WorkspaceDirs::default(),
+                backend: Backend::WhisperCli,
+                whisper_cli: "whisper-cli".to_string(),
+                model_name: None,
+                transcribe: TranscribeOptions::default(),
+                force: Vec::new(),
+                archive_previous: false,
+                restart: false,
+                translate: false,
+                translator: TranslatorConfig::default(),
+                outputs: OutputKind::ALL.to_vec(),
+                bilingual_layout: BilingualLayout::EnOverZh,
+                parallel: None,
+                vad: None,
+                language: LanguageOptions::default(),
+                split_mixed: false,
+                global_dedup: false,
+                dedup: DedupOptions::default(),
+                clips: None,
+                snapshots: None,
+                anki: None,
+                existing_subtitles: ExistingSubtitles::Prefer,
+                subtitle_formats: vec![SubtitleFormat::Srt, SubtitleFormat::Vtt],
+                from_transcripts: false,
+            },
+        }
+    }
+}
+
+pub struct PipelineConfigBuilder {
+    config: PipelineConfig,
+}
+
+impl PipelineConfigBuilder {
+    pub fn dirs(mut self, dirs: WorkspaceDirs) -> Self {
+        self.config.dirs = dirs;
+        self
+    }
+
+    pub fn backend(mut self, backend: Backend) -> Self {
+        self.config.backend = backend;
+        self
+    }
+
+    pub fn whisper_cli(mut self, binary: impl Into<String>) -> Self {
+        self.config.whisper_cli = binary.into();
+        self
+    }
+
+    pub fn model_name(mut self, model_name: Option<String>) -> Self {
+        self.config.model_name = model_name;
+        self
+    }
+
+    pub fn transcribe_options(mut self, options: TranscribeOptions) -> Self {
+        self.config.transcribe = options;
+        self
+    }
+
+    pub fn force(mut self, force: Vec<Artifact>) -> Self {
+        self.config.force = force;
+        self
+    }
+
+    pub fn archive_previous(mut self, archive_previous: bool) -> Self {
+        self.config.archive_previous = archive_previous;
+        self
+    }
+
+    pub fn restart(mut self, restart: bool) -> Self {
+        self.config.restart = restart;
+        self
+    }
+
+    pub fn translate(mut self, translate: bool) -> Self {
+        self.config.translate = translate;
+        self
+    }
+
+    pub fn translator(mut self, translator: TranslatorConfig) -> Self {
+        self.config.translator = translator;
+        self
+    }
+
+    pub fn outputs(mut self, outputs: Vec<OutputKind>) -> Self {
+        self.config.outputs = outputs;
+        self
+    }
+
+    pub fn bilingual_layout(mut self, layout: BilingualLayout) -> Self {
+        self.config.bilingual_layout = layout;
+        self
+    }
+
+    pub fn parallel(mut self, parallel: Option<ParallelOptions>) -> Self {
+        self.config.parallel = parallel;
+        self
+    }
+
+    pub fn vad(mut self, vad: Option<VadOptions>) -> Self {
+        self.config.vad = vad;
+        self
+    }
+
+    pub fn language(mut self, language: LanguageOptions) -> Self {
+        self.config.language = language;
+        self
+    }
+
+    pub fn split_mixed(mut self, split_mixed: bool) -> Self {
+        self.config.split_mixed = split_mixed;
+        self
+    }
+
+    pub fn global_dedup(mut self, global_dedup: bool) -> Self {
+        self.config.global_dedup = global_dedup;
+        self
+    }
+
+    pub fn dedup(mut self, dedup: DedupOptions) -> Self {
+        self.config.dedup = dedup;
+        self
+    }
+
+    pub fn clips(mut self, clips: Option<ClipOptions>) -> Self {
+        self.config.clips = clips;
+        self
+    }
+
+    pub fn snapshots(mut self, snapshots: Option<SnapshotOptions>) -> Self {
+        self.config.snapshots = snapshots;
+        self
+    }
+
+    pub fn anki(mut self, anki: Option<AnkiOptions>) -> Self {
+        self.config.anki = anki;
+        self
+    }
+
+    pub fn existing_subtitles(mut self, existing_subtitles: ExistingSubtitles) -> Self {
+        self.config.existing_subtitles = existing_subtitles;
+        self
+    }
+
+    pub fn subtitle_formats(mut self, formats: Vec<SubtitleFormat>) -> Self {
+        self.config.subtitle_formats = formats;
+        self
+    }
+
+    pub fn from_transcripts(mut self, from_transcripts: bool) -> Self {
+        self.config.from_transcripts = from_transcripts;
+        self
+    }
+
+    pub fn build(self) -> PipelineConfig {
+        self.config
+    }
+}
+
+/// workspace 中的固定子文件夹
+#[derive(Debug, Clone)]
+pub struct WorkspacePaths {
+    pub input_dir: PathBuf,
+    pub models_dir: PathBuf,
+    pub output_dir: PathBuf,
+}
+
+/// 单个文件处理完成后的各阶段结果
+#[derive(Debug, Clone)]
+pub struct ProcessedFile {
+    pub transcript: Transcript,
+    pub english: Vec<Segment>,
+    pub unique: Vec<Segment>,
+    /// 之前的文件中已经出现过、不再输出的句子（启用全局去重时）
+    pub known: Vec<Segment>,
+    /// 启用片段切割时的片段清单
+    pub clips: Option<ClipManifest>,
+}
+
+/// 翻译失败的segment，保存后可用 [`Pipeline::retry_failed_translations`] 重新翻译
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FailedTranslation {
+    pub start_ms: u32,
+    pub end_ms: u32,
+    pub text: String,
+    pub error: String,
+}
+
+/// 一次翻译的统计结果
+#[derive(Debug, Clone, Default)]
+pub struct TranslationReport {
+    pub total: usize,
+    pub cache_hits: usize,
+    pub translated: usize,
+    pub failed: Vec<FailedTranslation>,
+}
+
+pub struct Pipeline {
+    config: PipelineConfig,
+    language_filter: LanguageFilter,
+}
+
+impl Pipeline {
+    pub fn new(config: PipelineConfig) -> Self {
+        let language_filter = LanguageFilter::new(config.language.clone());
+
+        Self {
+            config,
+            language_filter,
+        }
+    }
+
+    pub fn config(&self) -> &PipelineConfig {
+        &self.config
+    }
+
+    /// 获取workspace中的固定子文件夹路径
+    pub fn workspace_paths(&self) -> Result<WorkspacePaths> {
+        let workspace = &self.config.workspace;
+
+        // 确保workspace目录存在
+        if !workspace.exists() {
+            return Err(anyhow!("Workspace directory does not exist: {}", workspace.display()));
+        }
+
+        let dirs = &self.config.dirs;
+        let input_dir = workspace.join(&dirs.video2en_input);
+        let models_dir = workspace.join(&dirs.models);
+        let output_dir = workspace.join(&dirs.video2en_output);
+
+        // 检查input和models目录是否存在
+        if !input_dir.exists() {
+            return Err(anyhow!("Input directory does not exist: {}", input_dir.display()));
+        }
+        if !models_dir.exists() {
+            return Err(anyhow!("Models directory does not exist: {}", models_dir.display()));
+        }
+
+        // 只创建output目录（如果不存在）
+        fs::create_dir_all(&output_dir).context("Failed to create output directory")?;
+
+        Ok(WorkspacePaths {
+            input_dir,
+            models_dir,
+            output_dir,
+        })
+    }
+
+    /// 获取输入文件路径列表（从workspace/input/目录中查找）
+    pub fn input_files(&self) -> Result<Vec<PathBuf>> {
+        let input_dir = self.workspace_paths()?.input_dir;
+
+        // 查找input目录中的视频/音频文件
+        let mut video_files = Vec::new();
+        for entry in fs::read_dir(&input_dir).context(format!("Failed to read input directory: {}", input_dir.display()))? {
+            let entry = entry.context("Failed to read directory entry")?;
+            let path = entry.path();
+
+            if path.is_file() {
+                if let Some(extension) = path.extension() {
+                    let ext = extension.to_string_lossy().to_lowercase();
+                    if MEDIA_EXTENSIONS.contains(&ext.as_str()) {
+                        video_files.push(path);
+                    }
+                }
+            }
+        }
+
+        if video_files.is_empty() {
+            return Err(anyhow!("No video/audio files found in input directory: {}", input_dir.display()));
+        }
+
+        video_files.sort();
+        check_output_folders(&video_files)?;
+        Ok(video_files)
+    }
+
+    /// 获取模型文件路径（从workspace/models/目录中查找）
+    pub fn model_file(&self) -> Result<PathBuf> {
+        let models_dir = self.workspace_paths()?.models_dir;
+        let model_name = self.config.model_name.as_deref().unwrap_or("ggml-large.bin");
+        resolve_model_file(&models_dir, model_name)
+    }
+
+    /// 依次处理workspace中的所有输入文件；单个文件失败时记录到状态文件并继续处理其余文件
+    pub async fn run(&self) -> Result<()> {
+        // Check ffmpeg availability
+        check_ffmpeg()?;
+
+        // 获取所有输入文件
+        let input_files = self.input_files()?;
+
+        println!("📁 找到 {} 个输入文件", input_files.len());
+
+        let output_dir = self.workspace_paths()?.output_dir;
+        let state_path = self.state_path(&output_dir);
+
+        // 默认在原输出目录中就地更新；--archive-previous 时先把旧的输出目录整体重命名保留。
+        // 复用已有转写结果时保留输出目录，也不需要识别后端
+        let transcriber = if self.config.from_transcripts {
+            None
+        } else {
+            if self.config.archive_previous {
+                self.prepare_output_dir(&output_dir)?;
+            }
+            Some(self.create_transcriber()?)
+        };
+        let mut state = StateManifest::open(&state_path)?;
+
+        // 每个文件的新句子数和重复句子数，用于全局去重报告
+        let mut sentence_report = Vec::new();
+        let mut skipped = 0;
+        let mut failures = Vec::new();
+
+        // 循环处理每个输入文件
+        for (index, input_file) in input_files.iter().enumerate() {
+            println!("\n🎬 处理文件 {}/{}: {}", index + 1, input_files.len(), input_file.display());
+            let name = input_file.file_name().unwrap_or_default().to_string_lossy().to_string();
+            let file_output_dir = input_output_dir(input_file, &output_dir);
+
+            let result = match &transcriber {
+                Some(transcriber) => {
+                    self.process_input(transcriber.as_ref(), input_file, &file_output_dir, &mut state).await
+                }
+                None => match self.load_transcript(input_file, &file_output_dir) {
+                    // 转写结果可能已被手动修改，由它生成的输出全部重新写出
+                    Ok(transcript) => self.process_transcript(transcript, Some(&mut state), true).await.map(Some),
+                    Err(e) => Err(e),
+                },
+            };
+
+            let processed = match result {
+                Ok(Some(processed)) => processed,
+                Ok(None) => {
+                    skipped += 1;
+                    continue;
+                }
+                Err(e) => {
+                    let stage = state.fail(&name, &e)?;
+                    println!("❌ 文件 {} 在 {} 阶段失败: {:#}", name, stage.as_str(), e);
+                    failures.push((name, stage, e));
+                    continue;
+                }
+            };
+
+            println!("✅ 文件 {} 处理完成!", name);
+            println!("📁 生成的文件:");
+            println!("   - {} (音频文件)", processed.transcript.audio_path.display());
+
+            sentence_report.push((name, processed.unique.len(), processed.known.len()));
+        }
+
+        if self.config.global_dedup {
+            println!("\n📚 全局句子索引报告 ({}):", self.sentence_index_path().display());
+            for (name, new, repeated) in &sentence_report {
+                println!("   - {}: 新句子 {}，之前出现过 {}", name, new, repeated);
+            }
+        }
+
+        println!(
+            "\n📊 处理结果: 完成 {}，已完成跳过 {}，失败 {} (状态文件: {})",
+            sentence_report.len(),
+            skipped,
+            failures.len(),
+            state_path.display()
+        );
+        if !failures.is_empty() {
+            for (name, stage, error) in &failures {
+                println!("   ❌ {} [{}]: {:#}", name, stage.as_str(), error);
+            }
+            return Err(anyhow!(
+                "{} of {} files failed; run again to resume from the failed stage",
+                failures.len(),
+                input_files.len()
+            ));
+        }
+
+        println!("\n🎉 所有文件处理完成！共处理了 {} 个文件", input_files.len());
+        Ok(())
+    }
+
+    /// 按状态文件处理一个输入文件：全部阶段都已完成且仍然有效时跳过并返回 `None`
+    pub async fn process_input(
+        &self,
+        transcriber: &dyn Transcriber,
+        input_file: &Path,
+        output_dir: &Path,
+        state: &mut StateManifest,
+    ) -> Result<Option<ProcessedFile>> {
+        let name = input_file.file_name().unwrap_or_default().to_string_lossy().to_string();
+        let (input_hash, input_metadata) = state.input_hash(&name, input_file)?;
+        let previous = state.file(&name).cloned();
+
+        let unchanged = previous.as_ref().map(|file| file.input_hash == input_hash).unwrap_or(false);
+        if unchanged && !self.config.restart && self.is_complete(state, &name, input_file, output_dir) {
+            println!("⏭️ 所有阶段均已完成，跳过（修改参数后会自动重新处理，使用 --force 强制重新生成）");
+            return Ok(None);
+        }
+
+        // 之前处理过的文件（包括翻译失败、未记录完成的）重新处理时，用新结果覆盖旧输出
+        let stale_outputs = previous.is_some();
+
+        state.begin(&name, &input_hash, input_metadata, self.config.restart)?;
+        fs::create_dir_all(output_dir).context(format!("Failed to create output directory: {}", output_dir.display()))?;
+        self.process_file(transcriber, input_file, output_dir, state, stale_outputs).await.map(Some)
+    }
+
+    /// 状态文件中该文件的所有阶段是否都已完成且仍然有效
+    fn is_complete(&self, state: &StateManifest, name: &str, input_file: &Path, output_dir: &Path) -> bool {
+        let audio_path = audio_path(input_file, output_dir);
+        let transcribe_settings = self.transcribe_settings();
+        let process_settings = self.process_settings();
+
+        self.config.force.is_empty()
+            && state.is_current(name, Stage::Extracted, Some(&audio_path), None)
+            && state.is_current(name, Stage::Transcribed, Some(&raw_transcript_path(&audio_path)), Some(&transcribe_settings))
+            && [Stage::Filtered, Stage::Translated, Stage::Written]
+                .into_iter()
+                .all(|stage| state.is_current(name, stage, None, Some(&process_settings)))
+            && state.stage_hash(name, Stage::Written).is_some_and(|hash| {
+                // 输出文件被删除或修改后重新写出
+                self.outputs_hash(&audio_path).is_ok_and(|current| current == hash)
+            })
+    }
+
+    /// 单个文件的完整流程：抽音 → 识别（或导入已有字幕） → 过滤 → 去重 → 翻译 → 输出；
+    /// 状态文件中仍然有效的抽音和识别结果直接复用；`stale_outputs` 为 true 时覆盖之前写出的输出
+    pub async fn process_file(
+        &self,
+        transcriber: &dyn Transcriber,
+        input_file: &Path,
+        output_dir: &Path,
+        state: &mut StateManifest,
+        stale_outputs: bool,
+    ) -> Result<ProcessedFile> {
+        let name = input_file.file_name().unwrap_or_default().to_string_lossy().to_string();
+
+        let audio_path = audio_path(input_file, output_dir);
+        if !self.config.force.contains(&Artifact::Audio) && state.is_current(&name, Stage::Extracted, Some(&audio_path), None) {
+            println!("⏭️ 复用已提取的音频: {}", audio_path.display());
+        } else {
+            extract_audio(input_file, output_dir)?;
+            state.complete(&name, Stage::Extracted, Some(hash_file(&audio_path)?), None)?;
+        }
+
+        let settings = self.transcribe_settings();
+        let raw_path = raw_transcript_path(&audio_path);
+        let reuse_transcript = !self.config.force.contains(&Artifact::Transcript)
+            && state.is_current(&name, Stage::Transcribed, Some(&raw_path), Some(&settings));
+        let transcript = if reuse_transcript {
+            println!("⏭️ 复用识别结果: {}", raw_path.display());
+            Transcript::read(&raw_path)?
+        } else {
+            let imported = match self.config.existing_subtitles {
+                ExistingSubtitles::Prefer => self.import_existing_subtitles(input_file, output_dir),
+                ExistingSubtitles::Ignore => None,
+            };
+            let transcript = match imported {
+                Some(imported) => {
+                    println!("📥 使用已有字幕，跳过语音识别: {}", imported.path.display());
+                    let mut transcript = Transcript::new(input_file.to_path_buf(), audio_path.clone(), imported.segments);
+                    transcript.imported_from = Some(imported.path);
+                    transcript
+                }
+                None => self.transcribe(transcriber, input_file, &audio_path, output_dir)?,
+            };
+
+            // 识别结果作为检查点保存，之后的阶段失败时无需重新识别
+            fs::write(&raw_path, transcript.to_json()?)
+                .context(format!("Failed to write transcript checkpoint: {}", raw_path.display()))?;
+            state.complete(&name, Stage::Transcribed, Some(hash_file(&raw_path)?), Some(&settings))?;
+            transcript
+        };
+
+        self.process_transcript(transcript, Some(state), stale_outputs).await
+    }
+
+    /// 导入已有的目标语言字幕；字幕无法解析或没有目标语言的句子时返回 `None`，改用语音识别
+    fn import_existing_subtitles(&self, input_file: &Path, output_dir: &Path) -> Option<ImportedSubtitles> {
+        match import_subtitles(input_file, output_dir, self.config.language.target) {
+            Ok(Some(imported)) if imported.segments.iter().any(|s| self.is_target_language(&s.text)) => Some(imported),
+            Ok(Some(imported)) => {
+                println!("⚠️ 已有字幕中没有目标语言的句子，改用语音识别: {}", imported.path.display());
+                None
+            }
+            Ok(None) => None,
+            Err(e) => {
+                println!("⚠️ 导入已有字幕失败，改用语音识别: {:#}", e);
+                None
+            }
+        }
+    }
+
+    /// 状态文件 `<输出目录>/video2en_state.json`
+    pub fn state_path(&self, output_dir: &Path) -> PathBuf {
+        output_dir.join("video2en_state.json")
+    }
+
+    /// 影响识别结果的参数指纹（不含线程数等只影响速度的参数）
+    fn transcribe_settings(&self) -> String {
+        let config = &self.config;
+        let transcribe = &config.transcribe;
+        hash_text(&format!(
+            "{:?}",
+            (
+                config.backend,
+                &config.model_name,
+                &transcribe.language,
+                transcribe.beam_size,
+                transcribe.best_of,
+                transcribe.temperature,
+                &transcribe.initial_prompt,
+                transcribe.translate,
+                &config.vad,
+                &config.parallel,
+                config.existing_subtitles,
+            )
+        ))
+    }
+
+    /// 影响过滤、翻译和输出的参数指纹
+    fn process_settings(&self) -> String {
+        let config = &self.config;
+        hash_text(&format!(
+            "{:?}",
+            (
+                (config.split_mixed, &config.language, &config.dedup, config.global_dedup),
+                (config.translate, config.translator.provider, &config.translator.source_lang, &config.translator.target_lang),
+                (&config.outputs, config.bilingual_layout, &config.subtitle_formats),
+                (&config.clips, &config.snapshots, &config.anki),
+            )
+        ))
+    }
+
+    /// 识别之后的阶段：过滤 → 去重 → 翻译 → 输出，提供状态文件时记录各阶段完成情况；
+    /// `stale_outputs` 表示之前写出的输出已过期（输入、识别结果或参数有变化），需要全部覆盖
+    pub async fn process_transcript(
+        &self,
+        mut transcript: Transcript,
+        mut state: Option<&mut StateManifest>,
+        stale_outputs: bool,
+    ) -> Result<ProcessedFile> {
+        let name = transcript.source.file_name().unwrap_or_default().to_string_lossy().to_string();
+        let settings = self.process_settings();
+        if let Some(state) = state.as_deref_mut() {
+            state.reset_from(&name, Stage::Filtered)?;
+        }
+
+        if self.config.split_mixed {
+            transcript.segments = self.split_mixed_segments(&transcript.segments);
+        }
+        self.tag_segments(&mut transcript.segments);
+        if self.config.global_dedup {
+            self.mark_known(&mut transcript)?;
+        }
+
+        let english: Vec<Segment> = transcript.segments.iter().filter(|s| s.is_target).cloned().collect();
+        let mut unique: Vec<Segment> = english.iter().filter(|s| !s.is_duplicate && !s.is_known).cloned().collect();
+        let known: Vec<Segment> = english.iter().filter(|s| !s.is_duplicate && s.is_known).cloned().collect();
+        self.print_stats(&transcript, &english, &unique);
+        let texts: Vec<&str> = unique.iter().map(|s| s.text.as_str()).collect();
+        complete_stage(state.as_deref_mut(), &name, Stage::Filtered, Some(hash_text(&texts.join("\n"))), &settings)?;
+
+        let mut failed_translations = 0;
+        if self.config.translate && !unique.is_empty() {
+            let report = self.translate(&mut unique).await?;
+            self.save_failed_translations(&failed_translations_path(&transcript.audio_path), &report.failed)?;
+            failed_translations = report.failed.len();
+
+            sync_translations(&mut transcript.segments, &unique);
+        }
+
+        // 有翻译失败时不记录翻译阶段完成，下次运行从缓存重新翻译并重写输出
+        if failed_translations == 0 {
+            let translations: Vec<&str> = unique.iter().filter_map(|s| s.translation.as_deref()).collect();
+            let translated = self.config.translate.then(|| hash_text(&translations.join("\n")));
+            complete_stage(state.as_deref_mut(), &name, Stage::Translated, translated, &settings)?;
+        }
+
+        if let Some(options) = &self.config.snapshots {
+            if !unique.is_empty() {
+                self.take_snapshots(&mut transcript, &mut unique, options, stale_outputs)?;
+            }
+        }
+
+        self.write_outputs(&transcript, &english, &unique, stale_outputs)?;
+
+        // Anki 卡片使用原声片段时，即使没有 --clips 也要切割
+        let clip_options = match (&self.config.clips, &self.config.anki) {
+            (Some(options), _) => Some(options.clone()),
+            (None, Some(anki)) if anki.audio == AnkiAudio::Clip => Some(ClipOptions::default()),
+            _ => None,
+        };
+        let clips = match &clip_options {
+            Some(options) if !unique.is_empty() => Some(self.write_clips(&transcript.audio_path, &unique, options, stale_outputs)?),
+            _ => None,
+        };
+
+        if let Some(anki) = &self.config.anki {
+            if !unique.is_empty() {
+                self.write_anki(&transcript, &unique, clips.as_ref(), anki, stale_outputs)?;
+            }
+        }
+        if failed_translations == 0 {
+            complete_stage(state, &name, Stage::Written, Some(self.outputs_hash(&transcript.audio_path)?), &settings)?;
+        } else if let Some(state) = state {
+            let error = anyhow!("{} translations failed; run again to retranslate them", failed_translations);
+            state.fail(&name, &error)?;
+            println!("⚠️ {} 条翻译失败，输出中缺少这些翻译，下次运行时会重新翻译并重写输出", failed_translations);
+        }
+
+        Ok(ProcessedFile {
+            transcript,
+            english,
+            unique,
+            known,
+            clips,
+        })
+    }
+
+    /// 读取输出目录中已有的 <name>.json 或 <name>.jsonl 转写结果；
+    /// 找不到时再查找旧版本直接写在输出根目录中的结果
+    pub fn load_transcript(&self, input_file: &Path, output_dir: &Path) -> Result<Transcript> {
+        let mut dirs = vec![output_dir.to_path_buf()];
+        dirs.extend(output_dir.parent().map(Path::to_path_buf));
+
+        for dir in &dirs {
+            let audio_path = audio_path(input_file, dir);
+            for kind in [OutputKind::Json, OutputKind::Jsonl] {
+                let path = output_path(&audio_path, kind);
+                if path.exists() {
+                    println!("📄 复用转写结果: {}", path.display());
+                    return Transcript::read(&path);
+                }
+            }
+        }
+
+        Err(anyhow!(
+            "No transcript JSON found for {} in {}",
+            input_file.display(),
+            output_dir.display()
+        ))
+    }
+
+    /// 拆分同时含有目标语言和其他语言的segment，如 "这个叫 take it easy 就是放轻松"
+    pub fn split_mixed_segments(&self, segments: &[Segment]) -> Vec<Segment> {
+        let split: Vec<Segment> = segments
+            .iter()
+            .flat_map(|segment| self.language_filter.split_mixed(segment))
+            .collect();
+        if split.len() > segments.len() {
+            println!("✂️ 拆分混合语言segments: {} → {}", segments.len(), split.len());
+        }
+        split
+    }
+
+    /// 为每个segment标注语言、置信度、是否目标语言以及是否重复
+    pub fn tag_segments(&self, segments: &mut [Segment]) {
+        for segment in segments.iter_mut() {
+            let detection = self.language_filter.detect(&segment.text);
+            segment.language = detection.language.map(|language| language.to_string());
+            segment.confidence = detection.confidence;
+            segment.is_target = detection.is_target;
+            segment.is_duplicate = false;
+            segment.is_known = false;
+            segment.merged.clear();
+        }
+
+        let target: Vec<usize> = (0..segments.len()).filter(|&i| segments[i].is_target).collect();
+        mark_duplicates(segments, &target, &self.config.dedup);
+    }
+
+    /// 对照全局句子索引标记之前其他文件中出现过的句子，并把本文件的新句子加入索引
+    pub fn mark_known(&self, transcript: &mut Transcript) -> Result<()> {
+        let mut index = SentenceIndex::open(&self.sentence_index_path())?;
+        let source = transcript.source.file_name().unwrap_or_default().to_string_lossy().to_string();
+
+        for segment in transcript.segments.iter_mut().filter(|s| s.is_target && !s.is_duplicate) {
+            segment.is_known = index.is_known(&segment.text, &source);
+            if !segment.is_known {
+                index.insert(&segment.text, &source)?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn sentence_index_path(&self) -> PathBuf {
+        self.config.workspace.join(&self.config.dirs.cache).join("sentences.jsonl")
+    }
+
+    /// 输出目录不为空时重命名为 *_backup
+    pub fn prepare_output_dir(&self, output_dir: &Path) -> Result<()> {
+        if !output_dir.exists() {
+            // 输出目录不存在，创建它
+            fs::create_dir_all(output_dir).context("Failed to create output directory")?;
+            println!("📁 创建输出目录: {}", output_dir.display());
+            return Ok(());
+        }
+
+        // 检查输出目录是否为空
+        let mut entries = fs::read_dir(output_dir).context("Failed to read output directory")?;
+        if entries.next().is_none() {
+            // 目录为空，直接使用
+            println!("📁 输出目录为空，直接使用: {}", output_dir.display());
+            return Ok(());
+        }
+
+        // 目录不为空，需要重命名
+        println!("📁 输出目录不为空，正在重命名: {}", output_dir.display());
+
+        let mut backup_name = output_dir.with_file_name(format!("{}_backup", output_dir.file_name().unwrap_or_default().to_string_lossy()));
+        let mut counter = 1;
+
+        // 处理多次重命名的情况
+        while backup_name.exists() {
+            backup_name = output_dir.with_file_name(format!("{}_backup_{}",
+                output_dir.file_name().unwrap_or_default().to_string_lossy(),
+                counter
+            ));
+            counter += 1;
+        }
+
+        // 重命名原目录
+        fs::rename(output_dir, &backup_name).context("Failed to rename output directory")?;
+        println!("📁 已重命名为: {}", backup_name.display());
+
+        // 创建新的输出目录
+        fs::create_dir_all(output_dir).context("Failed to create new output directory")?;
+        println!("📁 创建新的输出目录: {}", output_dir.display());
+
+        Ok(())
+    }
+
+    /// 根据配置创建语音识别后端：启用分块并行时包装为 [`ParallelTranscriber`]，
+    /// 启用 VAD 时再包装为 [`VadTranscriber`]（每个语音段内部再分块并行）
+    pub fn create_transcriber(&self) -> Result<Box<dyn Transcriber>> {
+        let mut options = self.config.transcribe.clone();
+        if let Some(parallel) = &self.config.parallel {
+            options.threads = parallel.threads_per_worker(options.threads);
+        }
+
+        let mut transcriber = self.create_backend(options)?;
+        if let Some(parallel) = &self.config.parallel {
+            transcriber = Box::new(ParallelTranscriber::new(transcriber, parallel.clone()));
+        }
+        Ok(match &self.config.vad {
+            Some(options) => Box::new(VadTranscriber::new(transcriber, options.clone())),
+            None => transcriber,
+        })
+    }
+
+    fn create_backend(&self, options: TranscribeOptions) -> Result<Box<dyn Transcriber>> {
+        let model = self.model_file()?;
+        match self.config.backend {
+            Backend::WhisperCli => {
+                let transcriber = WhisperCliTranscriber::new(self.config.whisper_cli.clone(), model, options);
+                transcriber.check_available()?;
+                Ok(Box::new(transcriber))
+            }
+            #[cfg(feature = "whisper-rs")]
+            Backend::WhisperRs => {
+                println!("📦 Loading model: {}", model.display());
+                Ok(Box::new(crate::transcriber::WhisperRsTranscriber::new(&model, options)?))
+            }
+            #[cfg(not(feature = "whisper-rs"))]
+            Backend::WhisperRs => Err(anyhow!(
+                "The whisper-rs backend is not available in this build. \
+                 Rebuild with: cargo build --release --features whisper-rs"
+            )),
+        }
+    }
+
+    pub fn transcribe(
+        &self,
+        transcriber: &dyn Transcriber,
+        source: &Path,
+        audio_path: &Path,
+        output_dir: &Path,
+    ) -> Result<Transcript> {
+        println!("🤖 Transcribing audio using {}...", transcriber.name());
+
+        let segments = transcriber.transcribe(audio_path, output_dir)?;
+
+        println!("✅ Transcribed {} text segments", segments.len());
+        Ok(Transcript::new(source.to_path_buf(), audio_path.to_path_buf(), segments))
+    }
+
+    /// 过滤目标语言segments
+    pub fn filter_target_language(&self, segments: &[Segment]) -> Vec<Segment> {
+        segments
+            .iter()
+            .filter(|segment| self.is_target_language(&segment.text))
+            .cloned()
+            .collect()
+    }
+
+    /// 按去重配置合并重复的segment，返回每组保留的一句
+    pub fn dedup(&self, segments: &[Segment]) -> Vec<Segment> {
+        let mut segments = segments.to_vec();
+        let candidates: Vec<usize> = (0..segments.len()).collect();
+        mark_duplicates(&mut segments, &candidates, &self.config.dedup);
+        segments.into_iter().filter(|segment| !segment.is_duplicate).collect()
+    }
+
+    fn print_stats(&self, transcript: &Transcript, english: &[Segment], unique: &[Segment]) {
+        let total_segments = transcript.segments.len();
+        let english_segments_count = english.len();
+        let unique_english_count = unique.len();
+        let known_count = transcript.segments.iter().filter(|s| s.is_known).count();
+        let duplicate_count = english_segments_count - unique_english_count - known_count;
+        let non_english_segments_count = total_segments - english_segments_count;
+
+        println!("📊 统计结果:");
+        println!("   - 总段落数: {}", total_segments);
+        println!("   - 英文段落数: {}", english_segments_count);
+        println!("   - 去重后英文段落数: {}", unique_english_count);
+        println!("   - 重复英文段落数: {}", duplicate_count);
+        if self.config.global_dedup {
+            println!("   - 之前文件中已出现的句子: {}", known_count);
+        }
+        println!("   - 非英文段落数: {}", non_english_segments_count);
+
+        if total_segments > 0 {
+            let english_percentage = (english_segments_count as f64 / total_segments as f64) * 100.0;
+            let unique_percentage = (unique_english_count as f64 / total_segments as f64) * 100.0;
+            println!("   - 英文比例: {:.1}%", english_percentage);
+            println!("   - 去重后英文比例: {:.1}%", unique_percentage);
+        }
+    }
+
+    /// 翻译segments：先查缓存，未命中的按并发数和限流速率请求翻译服务，失败时指数退避重试
+    pub async fn translate(&self, segments: &mut [Segment]) -> Result<TranslationReport> {
+        let config = &self.config.translator;
+        let provider = config.provider.as_str();
+        let target_lang = config.target_lang.as_str();
+
+        let mut cache = match config.cache_mode {
+            CacheMode::Off => None,
+            _ => Some(TranslationCache::open(&self.translation_cache_path(), config.cache_ttl)?),
+        };
+
+        let mut report = TranslationReport {
+            total: segments.len(),
+            ..Default::default()
+        };
+
+        // 先从缓存取结果，未命中的进入翻译队列
+        let mut pending = Vec::new();
+        for (i, segment) in segments.iter_mut().enumerate() {
+            if config.cache_mode != CacheMode::Refresh {
+                if let Some(result) = cache.as_ref().and_then(|c| c.get(provider, target_lang, &segment.text)) {
+                    apply_translation(segment, result);
+                    report.cache_hits += 1;
+                    continue;
+                }
+            }
+            pending.push(i);
+        }
+
+        println!("🌐 正在翻译英文内容 ({}): 缓存命中 {}，待翻译 {}", provider, report.cache_hits, pending.len());
+
+        if config.cache_mode == CacheMode::CacheOnly {
+            // 离线模式不访问翻译服务
+            for &i in &pending {
+                report.failed.push(failed_translation(&segments[i], "not in cache (cache-only mode)"));
+            }
+        } else if !pending.is_empty() {
+            let translator: Arc<dyn Translator> = Arc::from(create_translator(config)?);
+            let bucket = Arc::new(TokenBucket::new(config.rate_limit, config.concurrency));
+            let semaphore = Arc::new(Semaphore::new(config.concurrency.max(1)));
+
+            let mut tasks = JoinSet::new();
+            for &i in &pending {
+                let text = segments[i].text.clone();
+                let translator = translator.clone();
+                let bucket = bucket.clone();
+                let semaphore = semaphore.clone();
+                let retry = config.retry.clone();
+                tasks.spawn(async move {
+                    let _permit = semaphore.acquire_owned().await;
+                    let result = retry
+                        .run(|| async {
+                            bucket.acquire().await;
+                            translator.translate(&text).await
+                        })
+                        .await;
+                    (i, text, result)
+                });
+            }
+
+            let mut done = 0;
+            while let Some(joined) = tasks.join_next().await {
+                let (i, text, result) = joined.context("Translation task panicked")?;
+                done += 1;
+                print!("\r🔄 翻译进度: {}/{}", done, pending.len());
+                std::io::Write::flush(&mut std::io::stdout()).ok();
+
+                match result {
+                    Ok(result) => {
+                        if let Some(cache) = cache.as_mut() {
+                            cache.insert(provider, target_lang, &text, &result)?;
+                        }
+                        apply_translation(&mut segments[i], result);
+                        report.translated += 1;
+                    }
+                    Err(e) => {
+                        println!("\n⚠️ 翻译失败: {} - {:#}", text, e);
+                        segments[i].translation = None;
+                        report.failed.push(failed_translation(&segments[i], &format!("{:#}", e)));
+                    }
+                }
+            }
+            println!();
+        }
+
+        report.failed.sort_by_key(|failed| failed.start_ms);
+
+        println!(
+            "✅ 翻译完成! 共 {} 段: 缓存命中 {}，新翻译 {}，失败 {}",
+            report.total,
+            report.cache_hits,
+            report.translated,
+            report.failed.len()
+        );
+        for failed in &report.failed {
+            println!("   ❌ [{}] {} ({})", format_timestamp(failed.start_ms), failed.text, failed.error);
+        }
+
+        Ok(report)
+    }
+
+    /// 保存翻译失败列表；没有失败时删除旧的列表文件
+    fn save_failed_translations(&self, path: &Path, failed: &[FailedTranslation]) -> Result<()> {
+        if failed.is_empty() {
+            if path.exists() {
+                fs::remove_file(path).context(format!("Failed to remove {}", path.display()))?;
+            }
+            return Ok(());
+        }
+
+        let content = serde_json::to_string_pretty(failed).context("Failed to serialize failed translations")?;
+        fs::write(path, content).context(format!("Failed to write {}", path.display()))?;
+        println!("📄 翻译失败列表已保存到: {} (使用 --retry-failed-translations 重试)", path.display());
+        Ok(())
+    }
+
+    /// 只重新翻译之前失败的segments，成功结果写入翻译缓存，下次运行时直接命中
+    pub async fn retry_failed_translations(&self) -> Result<()> {
+        if matches!(self.config.translator.cache_mode, CacheMode::Off | CacheMode::CacheOnly) {
+            return Err(anyhow!("Retrying failed translations requires the translation cache (use --cache-mode read-write)"));
+        }
+
+        // 失败列表在各输入文件的子目录中（旧版本直接写在输出根目录）
+        let output_dir = self.workspace_paths()?.output_dir;
+        let mut report_files = Vec::new();
+        for entry in fs::read_dir(&output_dir)
+            .context(format!("Failed to read output directory: {}", output_dir.display()))?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+        {
+            let candidates = if entry.is_dir() {
+                fs::read_dir(&entry)?.filter_map(|e| e.ok().map(|e| e.path())).collect()
+            } else {
+                vec![entry]
+            };
+            report_files.extend(
+                candidates
+                    .into_iter()
+                    .filter(|path| path.to_string_lossy().ends_with(FAILED_TRANSLATIONS_SUFFIX)),
+            );
+        }
+        report_files.sort();
+
+        if report_files.is_empty() {
+            println!("✅ 没有需要重试的翻译");
+            return Ok(());
+        }
+
+        for path in report_files {
+            println!("\n🔁 重试翻译: {}", path.display());
+            let content = fs::read_to_string(&path).context(format!("Failed to read {}", path.display()))?;
+            let failed: Vec<FailedTranslation> = serde_json::from_str(&content)
+                .context(format!("Failed to parse {}", path.display()))?;
+
+            let mut segments: Vec<Segment> = failed
+                .iter()
+                .map(|f| Segment::new(f.start_ms, f.end_ms, f.text.clone()))
+                .collect();
+            let report = self.translate(&mut segments).await?;
+            self.save_failed_translations(&path, &report.failed)?;
+
+            // 重试成功的翻译已写入缓存，重新执行过滤之后的阶段，把它们写进输出并更新状态文件
+            if report.failed.len() < failed.len() {
+                self.rewrite_outputs(&path, &output_dir).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 按失败列表旁的识别结果检查点（或输出的转写结果）重新生成该文件的输出
+    async fn rewrite_outputs(&self, failed_path: &Path, output_dir: &Path) -> Result<()> {
+        let file_name = failed_path.file_name().unwrap_or_default().to_string_lossy().to_string();
+        let stem = file_name.trim_end_matches(FAILED_TRANSLATIONS_SUFFIX);
+        let audio_path = failed_path.with_file_name(format!("{}.wav", stem));
+
+        let candidates = [
+            raw_transcript_path(&audio_path),
+            output_path(&audio_path, OutputKind::Json),
+            output_path(&audio_path, OutputKind::Jsonl),
+        ];
+        let Some(transcript_path) = candidates.iter().find(|path| path.exists()) else {
+            println!("⚠️ 找不到 {} 的转写结果，请重新运行处理以更新输出", stem);
+            return Ok(());
+        };
+
+        println!("📝 更新输出: {}", transcript_path.display());
+        let transcript = Transcript::read(transcript_path)?;
+        let mut state = StateManifest::open(&self.state_path(output_dir))?;
+        self.process_transcript(transcript, Some(&mut state), true).await?;
+        Ok(())
+    }
+
+    /// 翻译缓存文件路径
+    pub fn translation_cache_path(&self) -> PathBuf {
+        self.config.workspace.join(&self.config.dirs.cache).join("translations.jsonl")
+    }
+
+    /// 写出配置中选择的所有输出文件，`stale_outputs` 为 true 时覆盖已存在的文件
+    pub fn write_outputs(&self, transcript: &Transcript, english: &[Segment], unique: &[Segment], stale_outputs: bool) -> Result<()> {
+        let audio_path = &transcript.audio_path;
+
+        // 写出全量字幕、英文字幕和英文纯文本
+        if self.wants_output(OutputKind::AllSubs) {
+            self.write_subtitles(&transcript.segments, audio_path, OutputKind::AllSubs, "Full", stale_outputs)?;
+        }
+        if self.wants_output(OutputKind::EnSubs) {
+            self.write_subtitles(english, audio_path, OutputKind::EnSubs, "English", stale_outputs)?;
+        }
+        if self.wants_output(OutputKind::EnTxt) {
+            self.write_txt(english, &output_path(audio_path, OutputKind::EnTxt), stale_outputs)?;
+        }
+        if self.wants_output(OutputKind::Json) {
+            self.write_output(Artifact::Json, &output_path(audio_path, OutputKind::Json), &transcript.to_json()?, "Transcript JSON", stale_outputs)?;
+        }
+        if self.wants_output(OutputKind::Jsonl) {
+            self.write_output(Artifact::Json, &output_path(audio_path, OutputKind::Jsonl), &transcript.to_jsonl()?, "Transcript JSON-lines", stale_outputs)?;
+        }
+
+        // 保存去重后的英文内容到文件
+        if !unique.is_empty() {
+            let output_file = output_path(audio_path, OutputKind::UniqueTxt);
+            if self.wants_output(OutputKind::UniqueTxt) {
+                self.save_unique_english(unique, &output_file, stale_outputs)?;
+            }
+
+            // 显示去重后的英文内容预览
+            println!("📝 去重后英文内容预览 (前10段):");
+            for (i, segment) in unique.iter().take(10).enumerate() {
+                println!("   {}. [{}] {}", i + 1, format_timestamp(segment.start_ms), segment.text);
+                if let Some(ref translation) = segment.translation {
+                    println!("      中文: {}", translation);
+                }
+            }
+            if unique.len() > 10 {
+                println!("   ... 还有 {} 段去重后的英文内容", unique.len() - 10);
+            }
+
+            if self.wants_output(OutputKind::UniqueTxt) {
+                println!("💾 去重后的英文内容已保存到: {}", output_file.display());
+            }
+
+            // 有翻译时写出双语文件
+            if self.config.translate {
+                self.write_bilingual_outputs(audio_path, unique, stale_outputs)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 配置中选择的输出文件，包括内容为空时不会写出的文件、片段清单和 Anki 牌组
+    fn output_files(&self, audio_path: &Path) -> Vec<PathBuf> {
+        let mut files = Vec::new();
+        for &kind in &self.config.outputs {
+            match kind {
+                OutputKind::AllSubs | OutputKind::EnSubs | OutputKind::BilingualSubs => {
+                    for format in &self.config.subtitle_formats {
+                        let path = subtitle_path(audio_path, kind, *format);
+                        if kind == OutputKind::BilingualSubs {
+                            files.push(track_path(&path, "en"));
+                            files.push(track_path(&path, "zh"));
+                        }
+                        files.push(path);
+                    }
+                }
+                _ => files.push(output_path(audio_path, kind)),
+            }
+        }
+        if self.config.clips.is_some() || self.config.anki.is_some() {
+            files.push(clip_paths(audio_path).1);
+        }
+        if self.config.anki.is_some() {
+            files.push(audio_path.with_extension("apkg"));
+        }
+        files
+    }
+
+    /// 输出文件的指纹：每个文件是否存在及其内容哈希，输出被删除或修改后随之变化
+    fn outputs_hash(&self, audio_path: &Path) -> Result<String> {
+        let mut lines = Vec::new();
+        for path in self.output_files(audio_path) {
+            let hash = if path.exists() { hash_file(&path)? } else { "-".to_string() };
+            lines.push(format!("{}\t{}", path.file_name().unwrap_or_default().to_string_lossy(), hash));
+        }
+        Ok(hash_text(&lines.join("\n")))
+    }
+
+    /// 写出中英对照的 TXT、字幕和 TSV
+    fn write_bilingual_outputs(&self, audio_path: &Path, segments: &[Segment], stale_outputs: bool) -> Result<()> {
+        let layout = self.config.bilingual_layout;
+
+        if self.wants_output(OutputKind::BilingualTxt) {
+            let content = match layout {
+                BilingualLayout::Separate => {
+                    let english: Vec<&str> = segments.iter().map(|s| s.text.as_str()).collect();
+                    let chinese: Vec<&str> = segments.iter().filter_map(|s| s.translation.as_deref()).collect();
+                    format!("{}\n\n{}\n", english.join("\n"), chinese.join("\n"))
+                }
+                _ => segments
+                    .iter()
+                    .map(|segment| {
+                        let lines: Vec<String> = bilingual_lines(segment, layout).into_iter().map(|line| line.text).collect();
+                        lines.join("\n") + "\n\n"
+                    })
+                    .collect(),
+            };
+            self.write_output(Artifact::Bilingual, &output_path(audio_path, OutputKind::BilingualTxt), &content, "Bilingual TXT", stale_outputs)?;
+        }
+
+        let subtitle_formats = if self.wants_output(OutputKind::BilingualSubs) {
+            self.config.subtitle_formats.as_slice()
+        } else {
+            &[]
+        };
+        for format in subtitle_formats {
+            let writer = format.writer();
+            let path = subtitle_path(audio_path, OutputKind::BilingualSubs, *format);
+
+            if layout == BilingualLayout::Separate {
+                // 英文轨和中文轨分别写入 .en/.zh 文件
+                let english: Vec<Cue> = segments.iter().map(Cue::from_segment).collect();
+                let chinese: Vec<Cue> = segments
+                    .iter()
+                    .filter_map(|s| {
+                        s.translation.as_ref().map(|translation| Cue {
+                            start_ms: s.start_ms,
+                            end_ms: s.end_ms,
+                            lines: vec![CueLine::translation(translation.clone())],
+                        })
+                    })
+                    .collect();
+                self.write_output(Artifact::Bilingual, &track_path(&path, "en"), &writer.write(&english), "Bilingual English track", stale_outputs)?;
+                self.write_output(Artifact::Bilingual, &track_path(&path, "zh"), &writer.write(&chinese), "Bilingual Chinese track", stale_outputs)?;
+            } else {
+                let cues: Vec<Cue> = segments
+                    .iter()
+                    .map(|segment| Cue {
+                        start_ms: segment.start_ms,
+                        end_ms: segment.end_ms,
+                        lines: bilingual_lines(segment, layout),
+                    })
+                    .collect();
+                self.write_output(Artifact::Bilingual, &path, &writer.write(&cues), "Bilingual subtitle", stale_outputs)?;
+            }
+        }
+
+        if self.wants_output(OutputKind::BilingualTsv) {
+            let mut content = String::from("start\tend\tenglish\tchinese\n");
+            for segment in segments {
+                content.push_str(&format!(
+                    "{}\t{}\t{}\t{}\n",
+                    format_timestamp(segment.start_ms),
+                    format_timestamp(segment.end_ms),
+                    tsv_field(&segment.text),
+                    tsv_field(segment.translation.as_deref().unwrap_or_default())
+                ));
+            }
+            self.write_output(Artifact::Bilingual, &output_path(audio_path, OutputKind::BilingualTsv), &content, "Bilingual TSV", stale_outputs)?;
+        }
+
+        Ok(())
+    }
+
+    /// 切出每句的音频片段并写出清单；清单已存在且未指定 --force 时沿用已有片段
+    fn write_clips(&self, audio_path: &Path, segments: &[Segment], options: &ClipOptions, stale_outputs: bool) -> Result<ClipManifest> {
+        let (clip_dir, manifest_path) = clip_paths(audio_path);
+        if manifest_path.exists() && !self.overwrite(Artifact::Clips, stale_outputs) {
+            println!("[skip] Audio clips already exist: {}", manifest_path.display());
+            return ClipManifest::read(&manifest_path);
+        }
+
+        println!("✂️ Cutting {} audio clips into: {}", segments.len(), clip_dir.display());
+        let manifest = extract_clips(audio_path, segments, options)?;
+        let content = serde_json::to_string_pretty(&manifest).context("Failed to serialize clip manifest")?;
+        fs::write(&manifest_path, content)
+            .context(format!("Failed to write clip manifest: {}", manifest_path.display()))?;
+        println!("📝 Writing clip manifest: {}", manifest_path.display());
+
+        Ok(manifest)
+    }
+
+    /// 从原始视频截取每句中点的画面，并把截图路径记录到segment上
+    fn take_snapshots(
+        &self,
+        transcript: &mut Transcript,
+        unique: &mut [Segment],
+        options: &SnapshotOptions,
+        stale_outputs: bool,
+    ) -> Result<()> {
+        if !has_video(&transcript.source)? {
+            println!("[skip] No video stream for snapshots: {}", transcript.source.display());
+            return Ok(());
+        }
+
+        println!("🖼️ Grabbing {} snapshots into: {}", unique.len(), snapshot_dir(&transcript.audio_path).display());
+        let snapshots = extract_snapshots(&transcript.source, &transcript.audio_path, unique, options, self.overwrite(Artifact::Snapshots, stale_outputs))?;
+
+        let by_segment: HashMap<(u32, String), PathBuf> = unique
+            .iter_mut()
+            .zip(snapshots)
+            .map(|(segment, snapshot)| {
+                segment.snapshot = Some(snapshot.clone());
+                ((segment.start_ms, segment.text.clone()), snapshot)
+            })
+            .collect();
+        for segment in transcript.segments.iter_mut() {
+            if let Some(snapshot) = by_segment.get(&(segment.start_ms, segment.text.clone())) {
+                segment.snapshot = Some(snapshot.clone());
+            }
+        }
+        Ok(())
+    }
+
+    /// 把去重后的句子导出为 `<name>.apkg`，牌组为 `<deck>::<文件名>`
+    fn write_anki(
+        &self,
+        transcript: &Transcript,
+        segments: &[Segment],
+        clips: Option<&ClipManifest>,
+        options: &AnkiOptions,
+        stale_outputs: bool,
+    ) -> Result<()> {
+        let audio_path = &transcript.audio_path;
+        let apkg_path = audio_path.with_extension("apkg");
+        if apkg_path.exists() && !self.overwrite(Artifact::Anki, stale_outputs) {
+            println!("[skip] Anki deck already exists: {}", apkg_path.display());
+            return Ok(());
+        }
+
+        let stem = audio_path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+        let source_name = transcript.source.file_name().unwrap_or_default().to_string_lossy().to_string();
+
+        // 片段文件名在各文件间重复（0001.wav…），加上文件名前缀后放入 Anki 媒体库
+        let mut clip_audio: HashMap<u32, AnkiMedia> = HashMap::new();
+        if let Some(manifest) = clips {
+            let base = audio_path.parent().unwrap_or(Path::new("."));
+            for clip in &manifest.clips {
+                let file_name = clip.file.file_name().unwrap_or_default().to_string_lossy();
+                clip_audio.insert(
+                    clip.start_ms,
+                    AnkiMedia {
+                        name: format!("{}_{}", stem, file_name),
+                        path: base.join(&clip.file),
+                    },
+                );
+            }
+        }
+
+        let tts_audio = match (options.audio, &options.tts_data) {
+            (AnkiAudio::Tts, Some(path)) => load_tts_audio(path)?,
+            (AnkiAudio::Tts, None) => return Err(anyhow!("Anki TTS audio requires --anki-tts-data")),
+            _ => HashMap::new(),
+        };
+
+        let mut deck = AnkiDeck::new(format!("{}::{}", options.deck, stem));
+        for segment in segments {
+            let audio = match options.audio {
+                AnkiAudio::Clip => clip_audio.get(&segment.start_ms).cloned().into_iter().collect(),
+                AnkiAudio::Tts => tts_audio.get(&normalize_text(&segment.text)).cloned().unwrap_or_default(),
+                AnkiAudio::None => Vec::new(),
+            };
+            deck.notes.push(AnkiNote {
+                english: segment.text.clone(),
+                translation: segment.translation.clone().unwrap_or_default(),
+                phonetic: segment.phonetic.clone().unwrap_or_default(),
+                source: format!("{} {}", source_name, format_timestamp(segment.start_ms)),
+                audio,
+            });
+        }
+
+        println!("🃏 Writing Anki deck ({} notes): {}", deck.notes.len(), apkg_path.display());
+        deck.write_apkg(&apkg_path)
+    }
+
+    /// 写出一个输出文件，已存在、未过期且未对该产物指定 --force 时跳过
+    fn write_output(&self, artifact: Artifact, path: &Path, content: &str, description: &str, stale_outputs: bool) -> Result<()> {
+        if path.exists() && !self.overwrite(artifact, stale_outputs) {
+            println!("[skip] {} already exists: {}", description, path.display());
+            return Ok(());
+        }
+
+        println!("📝 Writing {}: {}", description, path.display());
+        fs::write(path, content).context(format!("Failed to write {}: {}", description, path.display()))?;
+        Ok(())
+    }
+
+    /// 是否覆盖该产物已存在的文件：之前的输出已过期，或对该产物指定了 --force
+    fn overwrite(&self, artifact: Artifact, stale_outputs: bool) -> bool {
+        stale_outputs || self.config.force.contains(&artifact)
+    }
+
+    fn wants_output(&self, kind: OutputKind) -> bool {
+        self.config.outputs.contains(&kind)
+    }
+
+    /// 按配置的每种字幕格式写出一份字幕
+    fn write_subtitles(
+        &self,
+        segments: &[Segment],
+        audio_path: &Path,
+        kind: OutputKind,
+        description: &str,
+        stale_outputs: bool,
+    ) -> Result<()> {
+        let cues: Vec<Cue> = segments.iter().map(Cue::from_segment).collect();
+        for format in &self.config.subtitle_formats {
+            let writer = format.writer();
+            let description = format!("{} {}", description, writer.extension().to_uppercase());
+            self.write_output(kind.artifact(), &subtitle_path(audio_path, kind, *format), &writer.write(&cues), &description, stale_outputs)?;
+        }
+        Ok(())
+    }
+
+    fn write_txt(&self, segments: &[Segment], output_path: &Path, stale_outputs: bool) -> Result<()> {
+        if output_path.exists() && !self.overwrite(Artifact::Text, stale_outputs) {
+            println!("[skip] English TXT already exists: {}", output_path.display());
+            return Ok(());
+        }
+
+        println!("📄 Writing English TXT: {}", output_path.display());
+
+        let content: String = segments
+            .iter()
+            .map(|segment| segment.text.clone())
+            .collect::<Vec<_>>()
+            .join("\n\n");
+
+        fs::write(output_path, content)
+            .context(format!("Failed to write TXT file: {}", output_path.display()))?;
+
+        Ok(())
+    }
+
+    fn save_unique_english(&self, segments: &[Segment], output_path: &Path, stale_outputs: bool) -> Result<()> {
+        if output_path.exists() && !self.overwrite(Artifact::Text, stale_outputs) {
+            println!("[skip] 去重英文文件已存在: {}", output_path.display());
+            return Ok(());
+        }
+
+        println!("📄 保存去重后的英文内容到: {}", output_path.display());
+
+        let mut content = String::new();
+        for segment in segments {
+            content.push_str(&format!("{}\n", segment.text));
+        }
+
+        fs::write(output_path, content)
+            .context(format!("Failed to write unique English file: {}", output_path.display()))?;
+
+        Ok(())
+    }
+
+    pub fn is_target_language(&self, text: &str) -> bool {
+        self.language_filter.is_target(text)
+    }
+}
+
+pub fn check_ffmpeg() -> Result<()> {
+    which::which("ffmpeg").map_err(|_| {
+        anyhow!(
+            "ffmpeg not found in PATH. Please install ffmpeg:\n{}\n\
+             Run `video2en doctor` to check the whole environment",
+            ffmpeg_install_hint()
+        )
+    })?;
+    Ok(())
+}
+
+/// 用 ffmpeg 提取16kHz单声道WAV到输出目录
+pub fn extract_audio(input_path: &Path, output_dir: &Path) -> Result<PathBuf> {
+    if input_path.file_stem().is_none() {
+        return Err(anyhow!("Invalid input filename"));
+    }
+
+    // 创建音频文件路径：输出目录 + 输入文件名 + .wav
+    let audio_path = audio_path(input_path, output_dir);
+
+    // 确保音频文件的父目录存在
+    if let Some(parent) = audio_path.parent() {
+        fs::create_dir_all(parent).context("Failed to create audio output directory")?;
+    }
+
+    println!("🎵 Extracting audio from: {}", input_path.display());
+    println!("💾 Audio will be saved to: {}", audio_path.display());
+
+    let status = Command::new("ffmpeg")
+        .arg("-y")                   // Overwrite output
+        .arg("-i").arg(input_path)
+        .args([
+            "-vn",                   // No video
+            "-ac", "1",             // Mono
+            "-ar", "16000",         // 16kHz sample rate
+            "-f", "wav",            // WAV format
+        ])
+        .arg(&audio_path)
+        .status()
+        .context("Failed to execute ffmpeg")?;
+
+    if !status.success() {
+        return Err(anyhow!("ffmpeg failed with exit code: {}", status));
+    }
+
+    Ok(audio_path)
+}
+
+/// 输入文件的输出子目录 `<输出根目录>/<输入文件名>/`，该文件的所有产物都写在其中
+pub fn input_output_dir(input_path: &Path, output_root: &Path) -> PathBuf {
+    output_root.join(input_path.file_stem().unwrap_or_default())
+}
+
+/// 输入文件对应的音频文件 `<输出目录>/<输入文件名>.wav`
+pub fn audio_path(input_path: &Path, output_dir: &Path) -> PathBuf {
+    let input_stem = input_path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+    output_dir.join(format!("{}.wav", input_stem))
+}
+
+/// 识别结果检查点 `<name>.raw.json`，过滤和翻译前的完整转写结果
+pub fn raw_transcript_path(audio_path: &Path) -> PathBuf {
+    let stem = audio_path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+    audio_path.with_file_name(format!("{}.raw.json", stem))
+}
+
+/// 有状态文件时记录阶段完成
+fn complete_stage(
+    state: Option<&mut StateManifest>,
+    name: &str,
+    stage: Stage,
+    hash: Option<String>,
+    settings: &str,
+) -> Result<()> {
+    match state {
+        Some(state) => state.complete(name, stage, hash, Some(settings)),
+        None => Ok(()),
+    }
+}
+
+const FAILED_TRANSLATIONS_SUFFIX: &str = ".failed_translations.json";
+
+/// 翻译失败列表文件，与音频文件同目录
+pub fn failed_translations_path(audio_path: &Path) -> PathBuf {
+    audio_path.with_file_name(format!(
+        "{}{}",
+        audio_path.file_stem().unwrap_or_default().to_string_lossy(),
+        FAILED_TRANSLATIONS_SUFFIX
+    ))
+}
+
+/// 双语字幕的英文轨/中文轨文件：a.bilingual.srt -> a.bilingual.en.srt
+fn track_path(path: &Path, track: &str) -> PathBuf {
+    let extension = path.extension().unwrap_or_default().to_string_lossy().to_string();
+    path.with_extension(format!("{}.{}", track, extension))
+}
+
+/// 按排版方式组织一条segment的中英文行，没有翻译时只保留英文
+fn bilingual_lines(segment: &Segment, layout: BilingualLayout) -> Vec<CueLine> {
+    let english = CueLine::original(segment.text.clone());
+    match (&segment.translation, layout) {
+        (Some(chinese), BilingualLayout::ZhOverEn) => vec![CueLine::translation(chinese.clone()), english],
+        (Some(chinese), _) => vec![english, CueLine::translation(chinese.clone())],
+        (None, _) => vec![english],
+    }
+}
+
+/// 读取 txt2audio 的音频清单，按规范化文本索引女声和男声朗读
+fn load_tts_audio(data_path: &Path) -> Result<HashMap<String, Vec<AnkiMedia>>> {
+    let data = AudioData::read(data_path)?;
+    let audio_dir = data_path.parent().unwrap_or(Path::new(".")).join("audio");
+
+    // 清单中的路径相对于生成时的工作目录，找不到时到清单旁的 audio/ 目录中查找
+    let resolve = |audio: &str| {
+        let path = PathBuf::from(audio);
+        let path = if path.exists() {
+            path
+        } else {
+            audio_dir.join(path.file_name().unwrap_or_default())
+        };
+        AnkiMedia {
+            name: path.file_name().unwrap_or_default().to_string_lossy().to_string(),
+            path,
+        }
+    };
+
+    Ok(data
+        .entries
+        .iter()
+        .map(|entry| (normalize_text(&entry.text), vec![resolve(&entry.female_audio), resolve(&entry.male_audio)]))
+        .collect())
+}
+
+/// 把翻译结果写入segment，没有整句翻译时标记为未找到
+fn apply_translation(segment: &mut Segment, result: Translation) {
+    segment.translation = Some(result.translation.unwrap_or_else(|| "未找到翻译".to_string()));
+    segment.phonetic = result.phonetics.us.or(result.phonetics.uk);
+}
+
+/// 把翻译结果同步回全量segments：按时间轴和原文对应，去重时合并到保留句的重复句（包括相似度合并的）共用同一翻译
+fn sync_translations(segments: &mut [Segment], unique: &[Segment]) {
+    let mut translations: HashMap<(u32, u32, &str), &Segment> = HashMap::new();
+    for segment in unique.iter().filter(|s| s.translation.is_some()) {
+        translations.insert((segment.start_ms, segment.end_ms, segment.text.as_str()), segment);
+        for merged in &segment.merged {
+            translations.insert((merged.start_ms, merged.end_ms, merged.text.as_str()), segment);
+        }
+    }
+
+    for segment in segments.iter_mut().filter(|s| s.is_target) {
+        let translated = translations.get(&(segment.start_ms, segment.end_ms, segment.text.as_str()));
+        segment.translation = translated.and_then(|s| s.translation.clone());
+        segment.phonetic = translated.and_then(|s| s.phonetic.clone());
+    }
+}
+
+fn failed_translation(segment: &Segment, error: &str) -> FailedTranslation {
+    FailedTranslation {
+        start_ms: segment.start_ms,
+        end_ms: segment.end_ms,
+        text: segment.text.clone(),
+        error: error.to_string(),
+    }
+}
+
+/// 输出文件与音频文件同目录，命名为 <输入文件名>.<类型后缀>
+pub fn output_path(audio_path: &Path, kind: OutputKind) -> PathBuf {
+    audio_path.with_file_name(format!(
+        "{}.{}",
+        audio_path.file_stem().unwrap_or_default().to_string_lossy(),
+        kind.suffix()
+    ))
+}
+
+/// 字幕文件命名为 <输入文件名>.<类型后缀>.<格式扩展名>
+pub fn subtitle_path(audio_path: &Path, kind: OutputKind, format: SubtitleFormat) -> PathBuf {
+    let path = output_path(audio_path, kind);
+    let file_name = format!("{}.{}", path.file_name().unwrap_or_default().to_string_lossy(), format.writer().extension());
+    path.with_file_name(file_name)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::dedup::DedupMethod;
+    use crate::throttle::RetryPolicy;
+    use crate::translator::Provider;
+
+    #[test]
+    fn test_filter_and_dedup() {
+        let pipeline = Pipeline::new(PipelineConfig::builder(".").build());
+        let segments = vec![
+            Segment::new(0, 1000, "Take it easy."),
+            Segment::new(1000, 2000, "这个就是放轻松"),
+            Segment::new(2000, 3000, "take it easy"),
+            Segment::new(3000, 4000, "See you tomorrow!"),
+        ];
+
+        let english = pipeline.filter_target_language(&segments);
+        assert_eq!(english.len(), 3);
+
+        let unique = pipeline.dedup(&english);
+        let texts: Vec<&str> = unique.iter().map(|s| s.text.as_str()).collect();
+        assert_eq!(texts, vec!["Take it easy.", "See you tomorrow!"]);
+    }
+
+    #[test]
+    fn test_sync_translations() {
+        let pipeline = Pipeline::new(
+            PipelineConfig::builder(".")
+                .dedup(DedupOptions {
+                    method: DedupMethod::EditDistance,
+                    ..Default::default()
+                })
+                .build(),
+        );
+        // 相似度合并的重复句文本不同，也要拿到保留句的翻译
+        let mut segments = vec![
+            Segment::new(0, 1000, "Let's meet at the station tomorrow"),
+            Segment::new(1000, 2000, "我们明天在车站见面吧"),
+            Segment::new(2000, 3000, "Let's meet at the station tomorrow morning"),
+        ];
+        pipeline.tag_segments(&mut segments);
+        assert!(segments[2].is_duplicate);
+
+        let mut unique: Vec<Segment> = segments.iter().filter(|s| s.is_target && !s.is_duplicate).cloned().collect();
+        unique[0].translation = Some("明天在车站见吧".to_string());
+        sync_translations(&mut segments, &unique);
+        assert_eq!(segments[0].translation.as_deref(), Some("明天在车站见吧"));
+        assert_eq!(segments[1].translation, None);
+        assert_eq!(segments[2].translation.as_deref(), Some("明天在车站见吧"));
+    }
+
+    #[tokio::test]
+    async fn test_retry_failed_translations() {
+        let dir = tempfile::tempdir().unwrap();
+        let workspace = dir.path();
+        fs::create_dir_all(workspace.join("video2en_input")).unwrap();
+        fs::create_dir_all(workspace.join("models")).unwrap();
+        let dict_file = workspace.join("dict.tsv");
+        fs::write(&dict_file, "Take it easy.\t放轻松\n").unwrap();
+
+        let pipeline = Pipeline::new(
+            PipelineConfig::builder(workspace)
+                .translate(true)
+                .translator(TranslatorConfig {
+                    provider: Provider::Dict,
+                    dict_file: Some(dict_file.clone()),
+                    retry: RetryPolicy {
+                        max_retries: 0,
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                })
+                .build(),
+        );
+
+        let output_root = workspace.join("video2en_output");
+        let input_file = workspace.join("video2en_input").join("ep01.mp4");
+        let audio_path = audio_path(&input_file, &input_output_dir(&input_file, &output_root));
+        fs::create_dir_all(audio_path.parent().unwrap()).unwrap();
+        let transcript = Transcript::new(
+            input_file,
+            audio_path.clone(),
+            vec![
+                Segment::new(0, 1000, "Take it easy."),
+                Segment::new(1000, 2000, "See you tomorrow!"),
+            ],
+        );
+        fs::write(raw_transcript_path(&audio_path), transcript.to_json().unwrap()).unwrap();
+
+        // 词典里没有第二句，翻译失败：输出中缺少翻译，翻译阶段不记录完成
+        let mut state = StateManifest::open(&pipeline.state_path(&output_root)).unwrap();
+        state.complete("ep01.mp4", Stage::Extracted, None, None).unwrap();
+        state.complete("ep01.mp4", Stage::Transcribed, None, None).unwrap();
+        pipeline.process_transcript(transcript, Some(&mut state), false).await.unwrap();
+        let bilingual = subtitle_path(&audio_path, OutputKind::BilingualSubs, SubtitleFormat::Srt);
+        assert!(!fs::read_to_string(&bilingual).unwrap().contains("明天见"));
+        assert!(failed_translations_path(&audio_path).exists());
+        assert_eq!(state.file("ep01.mp4").unwrap().failed_stage, Some(Stage::Translated));
+
+        // 补上词典后重试：翻译写入缓存，输出和状态文件随之更新
+        fs::write(&dict_file, "Take it easy.\t放轻松\nSee you tomorrow!\t明天见\n").unwrap();
+        pipeline.retry_failed_translations().await.unwrap();
+        assert!(fs::read_to_string(&bilingual).unwrap().contains("明天见"));
+        assert!(!failed_translations_path(&audio_path).exists());
+
+        let state = StateManifest::open(&pipeline.state_path(&output_root)).unwrap();
+        let file = state.file("ep01.mp4").unwrap();
+        assert!(file.stages.contains_key(&Stage::Written));
+        assert_eq!(file.failed_stage, None);
+
+        // 写出阶段记录输出文件的指纹，删除输出后不再有效
+        let written = state.stage_hash("ep01.mp4", Stage::Written).unwrap();
+        assert_eq!(written, pipeline.outputs_hash(&audio_path).unwrap());
+        fs::remove_file(&bilingual).unwrap();
+        assert_ne!(written, pipeline.outputs_hash(&audio_path).unwrap());
+    }
+
+    #[test]
+    fn test_create_transcriber() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("video2en_input")).unwrap();
+        fs::create_dir_all(dir.path().join("models")).unwrap();
+        fs::write(dir.path().join("models").join("ggml-base.en.bin"), "").unwrap();
+
+        let config = |backend: Backend, whisper_cli: &str| {
+            PipelineConfig::builder(dir.path())
+                .backend(backend)
+                .whisper_cli(whisper_cli)
+                .model_name(Some("ggml-large.bin".to_string()))
+                .build()
+        };
+
+        // 指定的模型不存在时使用 models 目录中唯一的模型
+        let exe = std::env::current_exe().unwrap().to_string_lossy().to_string();
+        let pipeline = Pipeline::new(config(Backend::WhisperCli, &exe));
+        assert_eq!(pipeline.model_file().unwrap(), dir.path().join("models").join("ggml-base.en.bin"));
+        assert_eq!(pipeline.create_transcriber().unwrap().name(), exe);
+
+        let pipeline = Pipeline::new(config(Backend::WhisperCli, "video2en-missing-whisper-cli"));
+        let error = pipeline.create_transcriber().err().unwrap().to_string();
+        assert!(error.contains("video2en-missing-whisper-cli not found in PATH"), "{}", error);
+
+        #[cfg(not(feature = "whisper-rs"))]
+        {
+            let pipeline = Pipeline::new(config(Backend::WhisperRs, &exe));
+            let error = pipeline.create_transcriber().err().unwrap().to_string();
+            assert!(error.contains("--features whisper-rs"), "{}", error);
+        }
+    }
+}
@@ -0,0 +1,288 @@
+use anyhow::{anyhow, Result};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// 一条带时间轴的识别结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Segment {
+    pub start_ms: u32,
+    pub end_ms: u32,
+    pub text: String,
+    /// 检测到的语言
+    #[serde(default)]
+    pub language: Option<String>,
+    /// 语言检测置信度（0~1）
+    #[serde(default)]
+    pub confidence: Option<f64>,
+    /// 属于要保留的目标语言（旧版本的 JSON 中字段名为 `is_english`）
+    #[serde(default, alias = "is_english")]
+    pub is_target: bool,
+    /// 与之前的目标语言segment重复
+    #[serde(default)]
+    pub is_duplicate: bool,
+    /// 已在之前处理的其他文件中出现过（全局句子索引）
+    #[serde(default)]
+    pub is_known: bool,
+    #[serde(default)]
+    pub translation: Option<String>,
+    /// 翻译服务返回的音标（有道取自 `ec.word`，美音优先）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub phonetic: Option<String>,
+    /// segment中点的视频截图，相对输出目录
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snapshot: Option<PathBuf>,
+    /// 去重时合并到这一句的其他segment
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub merged: Vec<MergedSegment>,
+    /// 从混合语言的整行中拆出时，记录原始整行作为上下文
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context: Option<String>,
+}
+
+/// 被合并的重复segment
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MergedSegment {
+    pub start_ms: u32,
+    pub end_ms: u32,
+    pub text: String,
+    /// 与所在重复组第一句的相似度
+    pub similarity: f64,
+}
+
+impl Segment {
+    pub fn new(start_ms: u32, end_ms: u32, text: impl Into<String>) -> Self {
+        Self {
+            start_ms,
+            end_ms,
+            text: text.into(),
+            language: None,
+            confidence: None,
+            is_target: false,
+            is_duplicate: false,
+            is_known: false,
+            translation: None,
+            phonetic: None,
+            snapshot: None,
+            merged: Vec::new(),
+            context: None,
+        }
+    }
+}
+
+/// 解析SRT字幕内容
+pub fn parse_srt(srt_content: &str) -> Result<Vec<Segment>> {
+    let mut segments = Vec::new();
+    let content = srt_content.trim_start_matches('\u{feff}').replace("\r\n", "\n");
+
+    // SRT 以空行分隔字幕块：序号、时间轴、一行或多行文本
+    for block in content.split("\n\n") {
+        let mut lines = block.lines().map(|line| line.trim()).skip_while(|line| line.is_empty());
+
+        // 序号行可省略，直接以时间轴开头的块也接受
+        let mut timing = match lines.next() {
+            Some(line) => line,
+            None => continue,
+        };
+        if !timing.contains("-->") {
+            timing = match lines.next() {
+                Some(line) => line,
+                None => continue,
+            };
+        }
+
+        let (start, end) = timing
+            .split_once("-->")
+            .ok_or_else(|| anyhow!("Invalid SRT timing line: {}", timing))?;
+        let start_time = parse_timestamp(start.trim())?;
+        let end_time = parse_timestamp(end.trim())?;
+        let text = lines.collect::<Vec<_>>().join(" ").trim().to_string();
+
+        if !text.is_empty() {
+            segments.push(Segment::new(start_time, end_time, text));
+        }
+    }
+
+    Ok(segments)
+}
+
+/// 解析WebVTT字幕内容，忽略头部、NOTE/STYLE块、cue设置和行内标签
+pub fn parse_vtt(vtt_content: &str) -> Result<Vec<Segment>> {
+    let mut segments = Vec::new();
+    let content = vtt_content.trim_start_matches('\u{feff}').replace("\r\n", "\n");
+    let tag = Regex::new(r"<[^>]*>").unwrap();
+
+    for block in content.split("\n\n") {
+        let mut lines = block.lines().map(|line| line.trim()).skip_while(|line| line.is_empty());
+
+        // cue标识行可省略
+        let mut timing = match lines.next() {
+            Some(line) => line,
+            None => continue,
+        };
+        if !timing.contains("-->") {
+            timing = match lines.next() {
+                Some(line) if line.contains("-->") => line,
+                _ => continue,
+            };
+        }
+
+        let (start, rest) = timing
+            .split_once("-->")
+            .ok_or_else(|| anyhow!("Invalid VTT timing line: {}", timing))?;
+        // 结束时间后面可能跟着 `align:start position:10%` 等设置
+        let end = rest.split_whitespace().next().unwrap_or_default();
+        let start_time = parse_vtt_timestamp(start.trim())?;
+        let end_time = parse_vtt_timestamp(end)?;
+        let text = lines
+            .map(|line| tag.replace_all(line, "").to_string())
+            .collect::<Vec<_>>()
+            .join(" ")
+            .trim()
+            .to_string();
+
+        if !text.is_empty() {
+            segments.push(Segment::new(start_time, end_time, text));
+        }
+    }
+
+    Ok(segments)
+}
+
+/// 解析ASS/SSA字幕的 `[Events]` 中的 Dialogue 行，去掉 `{...}` 样式代码
+pub fn parse_ass(ass_content: &str) -> Result<Vec<Segment>> {
+    let mut segments = Vec::new();
+    let override_tags = Regex::new(r"\{[^}]*\}").unwrap();
+    let mut format: Vec<String> = Vec::new();
+    let mut in_events = false;
+
+    for line in ass_content.trim_start_matches('\u{feff}').lines().map(|line| line.trim()) {
+        if line.starts_with('[') {
+            in_events = line.eq_ignore_ascii_case("[events]");
+            continue;
+        }
+        if !in_events {
+            continue;
+        }
+
+        if let Some(fields) = line.strip_prefix("Format:") {
+            format = fields.split(',').map(|field| field.trim().to_lowercase()).collect();
+        } else if let Some(fields) = line.strip_prefix("Dialogue:") {
+            if format.is_empty() {
+                return Err(anyhow!("ASS Dialogue line before Format line"));
+            }
+            // Text 是最后一个字段，其中可能包含逗号
+            let values: Vec<&str> = fields.splitn(format.len(), ',').map(|value| value.trim()).collect();
+            let field = |name: &str| {
+                format
+                    .iter()
+                    .position(|f| f == name)
+                    .and_then(|i| values.get(i).copied())
+                    .ok_or_else(|| anyhow!("ASS Dialogue line missing {}: {}", name, line))
+            };
+
+            let start_time = parse_ass_timestamp(field("start")?)?;
+            let end_time = parse_ass_timestamp(field("end")?)?;
+            let text = override_tags
+                .replace_all(field("text")?, "")
+                .replace("\\N", " ")
+                .replace("\\n", " ")
+                .replace("\\h", " ")
+                .trim()
+                .to_string();
+
+            if !text.is_empty() {
+                segments.push(Segment::new(start_time, end_time, text));
+            }
+        }
+    }
+
+    Ok(segments)
+}
+
+/// WebVTT 时间戳，小时部分可省略（`MM:SS.mmm`）
+fn parse_vtt_timestamp(timestamp: &str) -> Result<u32> {
+    if timestamp.matches(':').count() == 1 {
+        parse_timestamp(&format!("00:{}", timestamp))
+    } else {
+        parse_timestamp(timestamp)
+    }
+}
+
+/// ASS 时间戳 `H:MM:SS.cc`（百分之一秒）
+fn parse_ass_timestamp(timestamp: &str) -> Result<u32> {
+    let (time, centiseconds) = timestamp
+        .split_once('.')
+        .ok_or_else(|| anyhow!("Invalid ASS timestamp: {}", timestamp))?;
+    let centiseconds: u32 = centiseconds.parse()?;
+    parse_timestamp(&format!("{},000", time)).map(|ms| ms + centiseconds * 10)
+}
+
+/// 解析 `HH:MM:SS,mmm` 格式的时间戳（也接受 `.` 作为毫秒分隔符）
+pub fn parse_timestamp(timestamp: &str) -> Result<u32> {
+    let parts: Vec<&str> = timestamp.split(':').collect();
+    if parts.len() != 3 {
+        return Err(anyhow!("Invalid timestamp format: {}", timestamp));
+    }
+
+    let hours: u32 = parts[0].parse()?;
+    let minutes: u32 = parts[1].parse()?;
+    let seconds_parts: Vec<&str> = parts[2].split([',', '.']).collect();
+    if seconds_parts.len() != 2 {
+        return Err(anyhow!("Invalid seconds format: {}", parts[2]));
+    }
+
+    let seconds: u32 = seconds_parts[0].parse()?;
+    let milliseconds: u32 = seconds_parts[1].parse()?;
+
+    Ok(hours * 3600000 + minutes * 60000 + seconds * 1000 + milliseconds)
+}
+
+/// 格式化为SRT时间戳 `HH:MM:SS,mmm`
+pub fn format_timestamp(ms: u32) -> String {
+    let seconds = ms / 1000;
+    let milliseconds = ms % 1000;
+    let hours = seconds / 3600;
+    let minutes = (seconds % 3600) / 60;
+    let secs = seconds % 60;
+
+    format!("{:02}:{:02}:{:02},{:03}", hours, minutes, secs, milliseconds)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_srt() {
+        let srt = "1\r\n00:00:01,200 --> 00:00:03,450\r\n Take it easy.\r\n\r\n\
+                   2\r\n00:01:02,000 --> 00:01:04,500\r\n 放轻松\r\n second line\r\n";
+
+        let segments = parse_srt(srt).unwrap();
+        assert_eq!(segments.len(), 2);
+        assert_eq!((segments[0].start_ms, segments[0].end_ms), (1200, 3450));
+        assert_eq!(segments[0].text, "Take it easy.");
+        assert_eq!((segments[1].start_ms, segments[1].end_ms), (62000, 64500));
+        assert_eq!(segments[1].text, "放轻松 second line");
+        assert_eq!(format_timestamp(segments[1].end_ms), "00:01:04,500");
+    }
+
+    #[test]
+    fn test_parse_vtt_and_ass() {
+        let vtt = "WEBVTT\n\nNOTE exported\n\nintro\n00:01.200 --> 00:03.450 align:start\n<c.yellow>Take</c> it easy.\n\n\
+                   01:00:00.000 --> 01:00:02.000\n放轻松\n";
+        let segments = parse_vtt(vtt).unwrap();
+        assert_eq!(segments.len(), 2);
+        assert_eq!((segments[0].start_ms, segments[0].end_ms), (1200, 3450));
+        assert_eq!(segments[0].text, "Take it easy.");
+        assert_eq!(segments[1].start_ms, 3_600_000);
+
+        let ass = "[Script Info]\nTitle: demo\n\n[Events]\n\
+                   Format: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text\n\
+                   Dialogue: 0,0:00:01.20,0:00:03.45,English,,0,0,0,,{\\i1}Take it easy,\\Nman.\n";
+        let segments = parse_ass(ass).unwrap();
+        assert_eq!(segments.len(), 1);
+        assert_eq!((segments[0].start_ms, segments[0].end_ms), (1200, 3450));
+        assert_eq!(segments[0].text, "Take it easy, man.");
+    }
+}
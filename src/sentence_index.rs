@@ -0,0 +1,120 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs::{self, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::text::normalize_text;
+
+/// 索引文件中的一行
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexEntry {
+    key: String,
+    pub text: String,
+    /// 第一次出现该句子的输入文件名
+    pub source: String,
+    /// 写入时间（Unix 秒）
+    pub first_seen: u64,
+}
+
+/// 以 JSON-lines 存储在 workspace 中的全局句子索引，跨文件、跨运行记录已经见过的句子
+pub struct SentenceIndex {
+    path: PathBuf,
+    entries: HashMap<String, IndexEntry>,
+}
+
+impl SentenceIndex {
+    /// 打开索引文件，不存在时视为空索引
+    pub fn open(path: &Path) -> Result<Self> {
+        let mut entries = HashMap::new();
+
+        if path.exists() {
+            let content = fs::read_to_string(path)
+                .context(format!("Failed to read sentence index: {}", path.display()))?;
+            // 同一句子只保留最早的记录；损坏的行直接跳过
+            for entry in content
+                .lines()
+                .filter_map(|line| serde_json::from_str::<IndexEntry>(line).ok())
+            {
+                entries.entry(entry.key.clone()).or_insert(entry);
+            }
+        }
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            entries,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn get(&self, text: &str) -> Option<&IndexEntry> {
+        self.entries.get(&normalize_text(text))
+    }
+
+    /// 句子是否已在其他输入文件中出现过；重新处理同一文件时不算已知
+    pub fn is_known(&self, text: &str, source: &str) -> bool {
+        self.get(text).map(|entry| entry.source != source).unwrap_or(false)
+    }
+
+    /// 记录新句子并追加到索引文件，已存在的句子不重复写入
+    pub fn insert(&mut self, text: &str, source: &str) -> Result<()> {
+        let key = normalize_text(text);
+        if self.entries.contains_key(&key) {
+            return Ok(());
+        }
+
+        let entry = IndexEntry {
+            key: key.clone(),
+            text: text.to_string(),
+            source: source.to_string(),
+            first_seen: SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0),
+        };
+
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).context("Failed to create cache directory")?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .context(format!("Failed to open sentence index: {}", self.path.display()))?;
+        writeln!(file, "{}", serde_json::to_string(&entry)?)
+            .context("Failed to write sentence index")?;
+
+        self.entries.insert(key, entry);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_sentence_index_across_runs() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("sentences.jsonl");
+
+        let mut index = SentenceIndex::open(&path).unwrap();
+        index.insert("Take it easy.", "ep01.mp4").unwrap();
+        index.insert("take it easy", "ep02.mp4").unwrap();
+        assert_eq!(index.len(), 1);
+
+        let index = SentenceIndex::open(&path).unwrap();
+        assert!(index.is_known("TAKE IT EASY!", "ep02.mp4"));
+        assert!(!index.is_known("Take it easy.", "ep01.mp4"));
+        assert!(!index.is_known("See you tomorrow", "ep02.mp4"));
+        assert_eq!(index.get("take it easy").unwrap().source, "ep01.mp4");
+    }
+}
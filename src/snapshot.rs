@@ -0,0 +1,178 @@
+use anyhow::{anyhow, Context, Result};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use crate::segment::Segment;
+
+/// 没有视频画面的输入扩展名，找不到 ffprobe 时按扩展名判断
+pub const AUDIO_ONLY_EXTENSIONS: &[&str] = &["mp3", "wav", "flac", "aac", "ogg", "m4a"];
+
+/// 截图的图片格式
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnapshotFormat {
+    Jpg,
+    Png,
+    Webp,
+}
+
+impl SnapshotFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            SnapshotFormat::Jpg => "jpg",
+            SnapshotFormat::Png => "png",
+            SnapshotFormat::Webp => "webp",
+        }
+    }
+}
+
+/// 截图参数
+#[derive(Debug, Clone)]
+pub struct SnapshotOptions {
+    /// 输出宽度，`None` 保持原始分辨率
+    pub width: Option<u32>,
+    /// 输出高度，`None` 按宽度等比缩放
+    pub height: Option<u32>,
+    pub format: SnapshotFormat,
+}
+
+impl Default for SnapshotOptions {
+    fn default() -> Self {
+        Self {
+            width: Some(640),
+            height: None,
+            format: SnapshotFormat::Jpg,
+        }
+    }
+}
+
+/// 截图目录 `<name>.frames/`，与音频文件同目录
+pub fn snapshot_dir(audio_path: &Path) -> PathBuf {
+    let stem = audio_path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+    audio_path.with_file_name(format!("{}.frames", stem))
+}
+
+/// 用 ffprobe 检查输入文件是否有视频流；音频文件中的封面图（attached_pic）不算视频
+pub fn has_video(input_file: &Path) -> Result<bool> {
+    if which::which("ffprobe").is_err() {
+        println!("⚠️ ffprobe not found in PATH, guessing video stream from the file extension");
+        let extension = input_file.extension().unwrap_or_default().to_string_lossy().to_lowercase();
+        return Ok(!AUDIO_ONLY_EXTENSIONS.contains(&extension.as_str()));
+    }
+
+    let output = Command::new("ffprobe")
+        .args([
+            "-v", "error",
+            "-select_streams", "v",
+            "-show_entries", "stream=index:stream_disposition=attached_pic",
+            "-of", "csv=p=0",
+        ])
+        .arg(input_file)
+        .output()
+        .context("Failed to execute ffprobe")?;
+
+    if !output.status.success() {
+        return Err(anyhow!(
+            "ffprobe failed for {}: {}",
+            input_file.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    // 每行形如 `0,0`：流序号，是否为封面图
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .any(|line| line.trim().split(',').nth(1) == Some("0")))
+}
+
+/// 截图文件名：segment 中点的毫秒时间戳，如 `00062500.jpg`；句子增删后同一时间点的截图仍可复用
+pub fn frame_file_name(midpoint_ms: u32, format: SnapshotFormat) -> String {
+    format!("{:08}.{}", midpoint_ms, format.extension())
+}
+
+/// 在每个segment的中点从原始视频截取一帧，返回相对音频文件目录的截图路径（与segments一一对应）
+pub fn extract_snapshots(
+    input_file: &Path,
+    audio_path: &Path,
+    segments: &[Segment],
+    options: &SnapshotOptions,
+    force: bool,
+) -> Result<Vec<PathBuf>> {
+    let frame_dir = snapshot_dir(audio_path);
+    fs::create_dir_all(&frame_dir).context(format!("Failed to create snapshot directory: {}", frame_dir.display()))?;
+    let frame_dir_name = PathBuf::from(frame_dir.file_name().unwrap_or_default());
+
+    let mut snapshots = Vec::new();
+    for segment in segments {
+        let midpoint_ms = segment.start_ms + segment.end_ms.saturating_sub(segment.start_ms) / 2;
+        let file_name = frame_file_name(midpoint_ms, options.format);
+        let frame_path = frame_dir.join(&file_name);
+
+        if !frame_path.exists() || force {
+            extract_frame(input_file, midpoint_ms, &frame_path, options)?;
+        }
+        snapshots.push(frame_dir_name.join(&file_name));
+    }
+
+    Ok(snapshots)
+}
+
+/// 用 ffmpeg 截取 `at_ms` 处的一帧
+pub fn extract_frame(input_file: &Path, at_ms: u32, output_path: &Path, options: &SnapshotOptions) -> Result<()> {
+    let mut command = Command::new("ffmpeg");
+    command
+        .args(["-y", "-loglevel", "error"])
+        .args(["-ss", &format!("{:.3}", at_ms as f64 / 1000.0)])   // 放在 -i 前快速定位
+        .arg("-i").arg(input_file)
+        .args(["-frames:v", "1"]);
+
+    if options.width.is_some() || options.height.is_some() {
+        // -2 表示按比例缩放并保持偶数尺寸
+        let dimension = |value: Option<u32>| value.map(|v| v.to_string()).unwrap_or_else(|| "-2".to_string());
+        command.args(["-vf", &format!("scale={}:{}", dimension(options.width), dimension(options.height))]);
+    }
+    if options.format == SnapshotFormat::Jpg {
+        command.args(["-q:v", "2"]);
+    }
+
+    let status = command
+        .arg(output_path)
+        .status()
+        .context("Failed to execute ffmpeg")?;
+
+    if !status.success() {
+        return Err(anyhow!("ffmpeg failed to grab frame at {} ms with exit code: {}", at_ms, status));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_frames() {
+        assert_eq!(frame_file_name(62_500, SnapshotFormat::Jpg), "00062500.jpg");
+        assert_eq!(frame_file_name(0, SnapshotFormat::Webp), "00000000.webp");
+
+        // 已有的截图按时间戳复用，不调用 ffmpeg；句子顺序变化不会错配截图
+        let dir = tempfile::tempdir().unwrap();
+        let audio_path = dir.path().join("movie.wav");
+        let frame_dir = snapshot_dir(&audio_path);
+        fs::create_dir_all(&frame_dir).unwrap();
+        for name in ["00001500.jpg", "00010000.jpg"] {
+            fs::write(frame_dir.join(name), name).unwrap();
+        }
+
+        let segments = vec![Segment::new(9000, 11000, "See you tomorrow!"), Segment::new(1000, 2000, "Take it easy.")];
+        let snapshots =
+            extract_snapshots(Path::new("missing.mp4"), &audio_path, &segments, &SnapshotOptions::default(), false).unwrap();
+        assert_eq!(
+            snapshots,
+            [PathBuf::from("movie.frames/00010000.jpg"), PathBuf::from("movie.frames/00001500.jpg")]
+        );
+        assert_eq!(fs::read_to_string(frame_dir.join("00010000.jpg")).unwrap(), "00010000.jpg");
+    }
+}
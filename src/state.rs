@@ -0,0 +1,310 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs::{self, File},
+    io::Read,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// 单个输入文件的处理阶段，按执行顺序排列
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Stage {
+    /// 已提取 16kHz WAV
+    Extracted,
+    /// 已识别（或导入已有字幕），检查点为 `<name>.raw.json`
+    Transcribed,
+    /// 已完成语言过滤和去重
+    Filtered,
+    /// 已翻译（未启用翻译时同样记录）
+    Translated,
+    /// 已写出全部输出文件
+    Written,
+}
+
+impl Stage {
+    pub const ALL: [Stage; 5] = [
+        Stage::Extracted,
+        Stage::Transcribed,
+        Stage::Filtered,
+        Stage::Translated,
+        Stage::Written,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Stage::Extracted => "extracted",
+            Stage::Transcribed => "transcribed",
+            Stage::Filtered => "filtered",
+            Stage::Translated => "translated",
+            Stage::Written => "written",
+        }
+    }
+}
+
+/// 一个阶段完成时的记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StageRecord {
+    /// 阶段产物的内容哈希（没有单独产物时为空）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hash: Option<String>,
+    /// 影响该阶段结果的参数指纹，参数变化后阶段需要重新执行
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub settings: Option<String>,
+    pub completed_at: u64,
+}
+
+/// 一个输入文件的处理状态
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FileState {
+    /// 输入文件的内容哈希，文件变化后所有阶段重新执行
+    pub input_hash: String,
+    /// 计算哈希时输入文件的大小和修改时间，都没变时沿用 `input_hash`，不再完整读取文件
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_metadata: Option<InputMetadata>,
+    #[serde(default)]
+    pub stages: BTreeMap<Stage, StageRecord>,
+    /// 最近一次失败的阶段和错误信息，成功处理后清除
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub failed_stage: Option<Stage>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// 输入文件的大小和修改时间（纳秒）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InputMetadata {
+    pub size: u64,
+    pub modified_ns: u64,
+}
+
+impl InputMetadata {
+    pub fn read(path: &Path) -> Result<Self> {
+        let metadata = fs::metadata(path).context(format!("Failed to read file metadata: {}", path.display()))?;
+        let modified = metadata
+            .modified()
+            .context(format!("Failed to read modification time: {}", path.display()))?;
+        Ok(Self {
+            size: metadata.len(),
+            modified_ns: modified.duration_since(UNIX_EPOCH).map(|d| d.as_nanos() as u64).unwrap_or(0),
+        })
+    }
+}
+
+/// 输出目录中的 `video2en_state.json`，记录每个输入文件各阶段的完成情况，用于中断或失败后续跑
+#[derive(Debug)]
+pub struct StateManifest {
+    path: PathBuf,
+    files: BTreeMap<String, FileState>,
+}
+
+impl StateManifest {
+    /// 打开状态文件，不存在时视为空
+    pub fn open(path: &Path) -> Result<Self> {
+        let files = if path.exists() {
+            let content = fs::read_to_string(path)
+                .context(format!("Failed to read state manifest: {}", path.display()))?;
+            serde_json::from_str(&content).context(format!("Invalid state manifest: {}", path.display()))?
+        } else {
+            BTreeMap::new()
+        };
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            files,
+        })
+    }
+
+    pub fn file(&self, name: &str) -> Option<&FileState> {
+        self.files.get(name)
+    }
+
+    /// 输入文件的内容哈希：大小和修改时间与上次记录相同时直接使用记录的哈希，否则完整计算
+    pub fn input_hash(&self, name: &str, path: &Path) -> Result<(String, InputMetadata)> {
+        let metadata = InputMetadata::read(path)?;
+        match self.files.get(name) {
+            Some(state) if state.input_metadata == Some(metadata) => Ok((state.input_hash.clone(), metadata)),
+            _ => Ok((hash_file(path)?, metadata)),
+        }
+    }
+
+    /// 开始处理一个文件：输入内容变化或 `restart` 时清除已完成的阶段
+    pub fn begin(&mut self, name: &str, input_hash: &str, metadata: InputMetadata, restart: bool) -> Result<()> {
+        let state = self.files.entry(name.to_string()).or_default();
+        if restart || state.input_hash != input_hash {
+            state.stages.clear();
+        }
+        state.input_hash = input_hash.to_string();
+        state.input_metadata = Some(metadata);
+        self.save()
+    }
+
+    /// 阶段是否已完成且仍然有效：参数未变化，产物（如有）仍存在且内容未变
+    pub fn is_current(&self, name: &str, stage: Stage, artifact: Option<&Path>, settings: Option<&str>) -> bool {
+        let Some(record) = self.files.get(name).and_then(|state| state.stages.get(&stage)) else {
+            return false;
+        };
+        if record.settings.as_deref() != settings {
+            return false;
+        }
+        match (artifact, &record.hash) {
+            (Some(path), Some(hash)) => hash_file(path).map(|current| &current == hash).unwrap_or(false),
+            _ => true,
+        }
+    }
+
+    /// 阶段记录中的产物哈希，阶段未完成或没有记录哈希时为 `None`
+    pub fn stage_hash(&self, name: &str, stage: Stage) -> Option<&str> {
+        self.files.get(name)?.stages.get(&stage)?.hash.as_deref()
+    }
+
+    /// 清除 `stage` 及之后阶段的记录，这些阶段将重新执行
+    pub fn reset_from(&mut self, name: &str, stage: Stage) -> Result<()> {
+        if let Some(state) = self.files.get_mut(name) {
+            state.stages.retain(|&recorded, _| recorded < stage);
+        }
+        self.save()
+    }
+
+    /// 记录阶段完成；之后的阶段依赖本阶段的结果，一并清除
+    pub fn complete(&mut self, name: &str, stage: Stage, hash: Option<String>, settings: Option<&str>) -> Result<()> {
+        let state = self.files.entry(name.to_string()).or_default();
+        state.stages.retain(|&recorded, _| recorded < stage);
+        state.stages.insert(
+            stage,
+            StageRecord {
+                hash,
+                settings: settings.map(str::to_string),
+                completed_at: SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0),
+            },
+        );
+        if stage == Stage::Written {
+            state.failed_stage = None;
+            state.error = None;
+        }
+        self.save()
+    }
+
+    /// 记录失败：失败的阶段是第一个尚未完成的阶段
+    pub fn fail(&mut self, name: &str, error: &anyhow::Error) -> Result<Stage> {
+        let state = self.files.entry(name.to_string()).or_default();
+        let stage = Stage::ALL
+            .into_iter()
+            .find(|stage| !state.stages.contains_key(stage))
+            .unwrap_or(Stage::Written);
+        state.failed_stage = Some(stage);
+        state.error = Some(format!("{:#}", error));
+        self.save()?;
+        Ok(stage)
+    }
+
+    /// 原子地写回状态文件，避免中断时留下半个文件
+    pub fn save(&self) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).context("Failed to create output directory")?;
+        }
+        let temp_path = self.path.with_extension("json.tmp");
+        fs::write(&temp_path, serde_json::to_string_pretty(&self.files)?)
+            .context(format!("Failed to write state manifest: {}", temp_path.display()))?;
+        fs::rename(&temp_path, &self.path)
+            .context(format!("Failed to write state manifest: {}", self.path.display()))?;
+        Ok(())
+    }
+}
+
+/// 文件内容哈希：整个文件内容的 MD5，按块读取。复制或同步后时间变了但内容相同的文件不会重新处理，
+/// 文件任何位置的修改都会被发现
+pub fn hash_file(path: &Path) -> Result<String> {
+    let mut file = File::open(path).context(format!("Failed to open file for hashing: {}", path.display()))?;
+    let mut context = md5::Context::new();
+    let mut buffer = vec![0; 1024 * 1024];
+    loop {
+        let read = file
+            .read(&mut buffer)
+            .context(format!("Failed to read file for hashing: {}", path.display()))?;
+        if read == 0 {
+            break;
+        }
+        context.consume(&buffer[..read]);
+    }
+
+    Ok(format!("{:x}", context.compute()))
+}
+
+/// 文本内容哈希，用于没有单独产物文件的阶段和参数指纹
+pub fn hash_text(text: &str) -> String {
+    format!("{:x}", md5::compute(text))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_state_manifest_resume() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("video2en_state.json");
+        let audio = dir.path().join("ep01.wav");
+        fs::write(&audio, b"RIFF audio").unwrap();
+
+        let metadata = InputMetadata::read(&audio).unwrap();
+        let mut state = StateManifest::open(&path).unwrap();
+        state.begin("ep01.mp4", "input-v1", metadata, false).unwrap();
+        state.complete("ep01.mp4", Stage::Extracted, Some(hash_file(&audio).unwrap()), None).unwrap();
+        state.complete("ep01.mp4", Stage::Transcribed, None, Some("model-a")).unwrap();
+        let failed = state.fail("ep01.mp4", &anyhow::anyhow!("network down")).unwrap();
+        assert_eq!(failed, Stage::Filtered);
+
+        // 重新打开后从失败的阶段继续
+        let mut state = StateManifest::open(&path).unwrap();
+        assert_eq!(state.file("ep01.mp4").unwrap().error.as_deref(), Some("network down"));
+        assert!(state.is_current("ep01.mp4", Stage::Extracted, Some(&audio), None));
+        assert!(state.is_current("ep01.mp4", Stage::Transcribed, None, Some("model-a")));
+        assert!(!state.is_current("ep01.mp4", Stage::Transcribed, None, Some("model-b")));
+        assert!(!state.is_current("ep01.mp4", Stage::Filtered, None, None));
+
+        // 产物被修改后阶段失效
+        fs::write(&audio, b"RIFF other audio").unwrap();
+        assert!(!state.is_current("ep01.mp4", Stage::Extracted, Some(&audio), None));
+
+        // 哈希覆盖整个文件：大文件中间的修改也能发现
+        let mut content = vec![0u8; 3 * 1024 * 1024];
+        fs::write(&audio, &content).unwrap();
+        let before = hash_file(&audio).unwrap();
+        content[3 * 1024 * 1024 / 2] = 1;
+        fs::write(&audio, &content).unwrap();
+        assert_ne!(hash_file(&audio).unwrap(), before);
+
+        // 重新完成前面的阶段会清除之后的阶段；输入变化时全部重来
+        state.complete("ep01.mp4", Stage::Extracted, None, None).unwrap();
+        assert!(!state.is_current("ep01.mp4", Stage::Transcribed, None, Some("model-a")));
+        state.begin("ep01.mp4", "input-v2", metadata, false).unwrap();
+        assert!(state.file("ep01.mp4").unwrap().stages.is_empty());
+    }
+
+    #[test]
+    fn test_input_hash_metadata() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("ep01.mp4");
+        fs::write(&input, b"video v1").unwrap();
+        let mut state = StateManifest::open(&dir.path().join("video2en_state.json")).unwrap();
+
+        let (hash, metadata) = state.input_hash("ep01.mp4", &input).unwrap();
+        assert_eq!(hash, hash_file(&input).unwrap());
+        state.begin("ep01.mp4", &hash, metadata, false).unwrap();
+
+        // 大小和修改时间都没变时不重新读取文件，沿用记录的哈希
+        let modified = fs::metadata(&input).unwrap().modified().unwrap();
+        fs::write(&input, b"video v2").unwrap();
+        File::options().write(true).open(&input).unwrap().set_modified(modified).unwrap();
+        assert_eq!(state.input_hash("ep01.mp4", &input).unwrap().0, hash);
+
+        // 修改时间变化后重新计算
+        let later = modified + std::time::Duration::from_secs(60);
+        File::options().write(true).open(&input).unwrap().set_modified(later).unwrap();
+        assert_eq!(state.input_hash("ep01.mp4", &input).unwrap().0, hash_file(&input).unwrap());
+        assert_ne!(state.input_hash("ep01.mp4", &input).unwrap().0, hash);
+    }
+}
@@ -0,0 +1,333 @@
+use crate::segment::{format_timestamp, Segment};
+
+/// 字幕行的角色，ASS/TTML 据此选择不同样式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineStyle {
+    /// 识别出的原文（英文）
+    Original,
+    /// 翻译（中文）
+    Translation,
+}
+
+#[derive(Debug, Clone)]
+pub struct CueLine {
+    pub text: String,
+    pub style: LineStyle,
+}
+
+impl CueLine {
+    pub fn original(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            style: LineStyle::Original,
+        }
+    }
+
+    pub fn translation(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            style: LineStyle::Translation,
+        }
+    }
+}
+
+/// 一条字幕，可包含多行文本
+#[derive(Debug, Clone)]
+pub struct Cue {
+    pub start_ms: u32,
+    pub end_ms: u32,
+    pub lines: Vec<CueLine>,
+}
+
+impl Cue {
+    pub fn from_segment(segment: &Segment) -> Self {
+        Self {
+            start_ms: segment.start_ms,
+            end_ms: segment.end_ms,
+            lines: vec![CueLine::original(segment.text.clone())],
+        }
+    }
+
+    fn texts(&self) -> Vec<&str> {
+        self.lines.iter().map(|line| line.text.as_str()).collect()
+    }
+}
+
+/// 可选的字幕格式
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubtitleFormat {
+    /// SubRip (.srt)
+    Srt,
+    /// WebVTT (.vtt)，用于网页播放器
+    Vtt,
+    /// Advanced SubStation Alpha (.ass)，英文和中文使用不同样式
+    Ass,
+    /// Timed Text Markup Language (.ttml)
+    Ttml,
+    /// 歌词格式 (.lrc)
+    Lrc,
+}
+
+impl SubtitleFormat {
+    pub fn writer(&self) -> Box<dyn SubtitleWriter> {
+        match self {
+            SubtitleFormat::Srt => Box::new(SrtWriter),
+            SubtitleFormat::Vtt => Box::new(VttWriter),
+            SubtitleFormat::Ass => Box::new(AssWriter),
+            SubtitleFormat::Ttml => Box::new(TtmlWriter),
+            SubtitleFormat::Lrc => Box::new(LrcWriter),
+        }
+    }
+}
+
+/// 字幕格式化器
+pub trait SubtitleWriter {
+    /// 文件扩展名（不含 `.`）
+    fn extension(&self) -> &'static str;
+
+    fn write(&self, cues: &[Cue]) -> String;
+}
+
+pub struct SrtWriter;
+
+impl SubtitleWriter for SrtWriter {
+    fn extension(&self) -> &'static str {
+        "srt"
+    }
+
+    fn write(&self, cues: &[Cue]) -> String {
+        let mut content = String::new();
+        for (i, cue) in cues.iter().enumerate() {
+            content.push_str(&format!("{}\n", i + 1));
+            content.push_str(&format!("{} --> {}\n", format_timestamp(cue.start_ms), format_timestamp(cue.end_ms)));
+            content.push_str(&format!("{}\n\n", cue.texts().join("\n")));
+        }
+        content
+    }
+}
+
+pub struct VttWriter;
+
+impl SubtitleWriter for VttWriter {
+    fn extension(&self) -> &'static str {
+        "vtt"
+    }
+
+    fn write(&self, cues: &[Cue]) -> String {
+        let mut content = String::from("WEBVTT\n\n");
+        for cue in cues {
+            content.push_str(&format!(
+                "{} --> {}\n",
+                format_vtt_timestamp(cue.start_ms),
+                format_vtt_timestamp(cue.end_ms)
+            ));
+            content.push_str(&format!("{}\n\n", cue.texts().join("\n")));
+        }
+        content
+    }
+}
+
+const ASS_HEADER: &str = "[Script Info]
+ScriptType: v4.00+
+PlayResX: 1920
+PlayResY: 1080
+WrapStyle: 0
+ScaledBorderAndShadow: yes
+
+[V4+ Styles]
+Format: Name, Fontname, Fontsize, PrimaryColour, SecondaryColour, OutlineColour, BackColour, Bold, Italic, Underline, StrikeOut, ScaleX, ScaleY, Spacing, Angle, BorderStyle, Outline, Shadow, Alignment, MarginL, MarginR, MarginV, Encoding
+Style: English,Arial,56,&H00FFFFFF,&H000000FF,&H00000000,&H80000000,0,0,0,0,100,100,0,0,1,2,1,2,40,40,40,1
+Style: Chinese,Microsoft YaHei,48,&H0000E5FF,&H000000FF,&H00000000,&H80000000,0,0,0,0,100,100,0,0,1,2,1,2,40,40,40,134
+
+[Events]
+Format: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text
+";
+
+/// ASS 字幕：英文使用 `English` 样式，翻译使用 `Chinese` 样式
+pub struct AssWriter;
+
+impl AssWriter {
+    fn style_name(style: LineStyle) -> &'static str {
+        match style {
+            LineStyle::Original => "English",
+            LineStyle::Translation => "Chinese",
+        }
+    }
+}
+
+impl SubtitleWriter for AssWriter {
+    fn extension(&self) -> &'static str {
+        "ass"
+    }
+
+    fn write(&self, cues: &[Cue]) -> String {
+        let mut content = String::from(ASS_HEADER);
+        for cue in cues {
+            let Some(first) = cue.lines.first() else {
+                continue;
+            };
+
+            // 同一条字幕内换行后用 {\r样式} 切换样式
+            let mut text = escape_ass(&first.text);
+            for line in &cue.lines[1..] {
+                let style = if line.style == first.style {
+                    String::new()
+                } else {
+                    format!("{{\\r{}}}", Self::style_name(line.style))
+                };
+                text.push_str(&format!("\\N{}{}", style, escape_ass(&line.text)));
+            }
+
+            content.push_str(&format!(
+                "Dialogue: 0,{},{},{},,0,0,0,,{}\n",
+                format_ass_timestamp(cue.start_ms),
+                format_ass_timestamp(cue.end_ms),
+                Self::style_name(first.style),
+                text
+            ));
+        }
+        content
+    }
+}
+
+/// TTML 字幕，英文和翻译分别引用 `original`/`translation` 样式
+pub struct TtmlWriter;
+
+impl SubtitleWriter for TtmlWriter {
+    fn extension(&self) -> &'static str {
+        "ttml"
+    }
+
+    fn write(&self, cues: &[Cue]) -> String {
+        let mut content = String::from(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <tt xmlns=\"http://www.w3.org/ns/ttml\" xmlns:tts=\"http://www.w3.org/ns/ttml#styling\" xml:lang=\"en\">\n\
+             \x20 <head>\n\
+             \x20   <styling>\n\
+             \x20     <style xml:id=\"original\" tts:color=\"white\"/>\n\
+             \x20     <style xml:id=\"translation\" tts:color=\"yellow\"/>\n\
+             \x20   </styling>\n\
+             \x20 </head>\n\
+             \x20 <body>\n\
+             \x20   <div>\n",
+        );
+        for cue in cues {
+            let spans: Vec<String> = cue
+                .lines
+                .iter()
+                .map(|line| {
+                    let style = match line.style {
+                        LineStyle::Original => "original",
+                        LineStyle::Translation => "translation",
+                    };
+                    format!("<span style=\"{}\">{}</span>", style, escape_xml(&line.text))
+                })
+                .collect();
+            content.push_str(&format!(
+                "      <p begin=\"{}\" end=\"{}\">{}</p>\n",
+                format_vtt_timestamp(cue.start_ms),
+                format_vtt_timestamp(cue.end_ms),
+                spans.join("<br/>")
+            ));
+        }
+        content.push_str("    </div>\n  </body>\n</tt>\n");
+        content
+    }
+}
+
+/// LRC 歌词：每条字幕一行，多行文本用 ` / ` 连接，字幕间的空档写入空行
+pub struct LrcWriter;
+
+impl SubtitleWriter for LrcWriter {
+    fn extension(&self) -> &'static str {
+        "lrc"
+    }
+
+    fn write(&self, cues: &[Cue]) -> String {
+        let mut content = String::new();
+        for (i, cue) in cues.iter().enumerate() {
+            content.push_str(&format!("[{}]{}\n", format_lrc_timestamp(cue.start_ms), cue.texts().join(" / ")));
+
+            let next_start = cues.get(i + 1).map(|next| next.start_ms);
+            if next_start.is_none_or(|start| start > cue.end_ms) {
+                content.push_str(&format!("[{}]\n", format_lrc_timestamp(cue.end_ms)));
+            }
+        }
+        content
+    }
+}
+
+/// WebVTT 时间戳 `HH:MM:SS.mmm`
+pub fn format_vtt_timestamp(ms: u32) -> String {
+    format_timestamp(ms).replace(',', ".")
+}
+
+/// ASS 时间戳 `H:MM:SS.cc`（百分之一秒）
+pub fn format_ass_timestamp(ms: u32) -> String {
+    let centiseconds = ms / 10;
+    format!(
+        "{}:{:02}:{:02}.{:02}",
+        centiseconds / 360000,
+        (centiseconds / 6000) % 60,
+        (centiseconds / 100) % 60,
+        centiseconds % 100
+    )
+}
+
+/// LRC 时间戳 `MM:SS.cc`，分钟数可超过 59
+pub fn format_lrc_timestamp(ms: u32) -> String {
+    let centiseconds = ms / 10;
+    format!("{:02}:{:02}.{:02}", centiseconds / 6000, (centiseconds / 100) % 60, centiseconds % 100)
+}
+
+fn escape_ass(text: &str) -> String {
+    text.replace('\n', "\\N").replace('{', "(").replace('}', ")")
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_subtitle_writers() {
+        let cues = vec![
+            Cue {
+                start_ms: 1200,
+                end_ms: 3450,
+                lines: vec![CueLine::original("Take it easy."), CueLine::translation("放轻松")],
+            },
+            Cue {
+                start_ms: 3450,
+                end_ms: 3_723_010,
+                lines: vec![CueLine::original("Rock & roll")],
+            },
+        ];
+
+        let srt = SubtitleFormat::Srt.writer().write(&cues);
+        assert!(srt.starts_with("1\n00:00:01,200 --> 00:00:03,450\nTake it easy.\n放轻松\n\n2\n"));
+
+        let vtt = SubtitleFormat::Vtt.writer().write(&cues);
+        assert!(vtt.contains("00:00:01.200 --> 00:00:03.450\nTake it easy.\n放轻松\n"));
+
+        let ass = SubtitleFormat::Ass.writer().write(&cues);
+        assert!(ass.contains("Dialogue: 0,0:00:01.20,0:00:03.45,English,,0,0,0,,Take it easy.\\N{\\rChinese}放轻松\n"));
+        assert!(ass.contains("Dialogue: 0,0:00:03.45,1:02:03.01,English,,0,0,0,,Rock & roll\n"));
+
+        let ttml = SubtitleFormat::Ttml.writer().write(&cues);
+        assert!(ttml.contains(
+            "<p begin=\"00:00:01.200\" end=\"00:00:03.450\"><span style=\"original\">Take it easy.</span>\
+             <br/><span style=\"translation\">放轻松</span></p>"
+        ));
+        assert!(ttml.contains("Rock &amp; roll"));
+
+        let lrc = SubtitleFormat::Lrc.writer().write(&cues);
+        assert_eq!(lrc, "[00:01.20]Take it easy. / 放轻松\n[00:03.45]Rock & roll\n[62:03.01]\n");
+    }
+}
@@ -0,0 +1,157 @@
+use anyhow::{anyhow, Result};
+use std::{
+    future::Future,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// 解析每秒请求数：必须是大于 0 的有限数
+pub fn parse_rate(value: &str) -> Result<f64> {
+    let rate: f64 = value.trim().parse().map_err(|_| anyhow!("Invalid rate: {}", value))?;
+    if !rate.is_finite() || rate <= 0.0 {
+        return Err(anyhow!("Rate must be greater than 0 requests per second, got {}", value));
+    }
+    Ok(rate)
+}
+
+/// 令牌桶限流器：每秒补充 `rate` 个令牌，最多积攒 `capacity` 个
+pub struct TokenBucket {
+    rate: f64,
+    capacity: f64,
+    state: Mutex<(f64, Instant)>,
+}
+
+impl TokenBucket {
+    pub fn new(rate: f64, capacity: usize) -> Self {
+        let capacity = capacity.max(1) as f64;
+        Self {
+            rate: rate.max(0.001),
+            capacity,
+            state: Mutex::new((capacity, Instant::now())),
+        }
+    }
+
+    /// 等待直到取得一个令牌
+    pub async fn acquire(&self) {
+        loop {
+            let wait = {
+                let mut state = self.state.lock().unwrap();
+                let (tokens, last) = &mut *state;
+                let now = Instant::now();
+                *tokens = (*tokens + now.duration_since(*last).as_secs_f64() * self.rate).min(self.capacity);
+                *last = now;
+
+                if *tokens >= 1.0 {
+                    *tokens -= 1.0;
+                    return;
+                }
+                Duration::from_secs_f64((1.0 - *tokens) / self.rate)
+            };
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+/// 指数退避重试策略
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(10),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// 第 `attempt` 次重试前的等待时间
+    pub fn delay(&self, attempt: u32) -> Duration {
+        self.base_delay
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_delay)
+    }
+
+    /// 执行 `f`，遇到可重试的错误时按指数退避重试
+    pub async fn run<T, F, Fut>(&self, mut f: F) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let mut attempt = 0;
+        loop {
+            match f().await {
+                Ok(value) => return Ok(value),
+                Err(e) if attempt < self.max_retries && is_retryable(&e) => {
+                    tokio::time::sleep(self.delay(attempt)).await;
+                    attempt += 1;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+/// 网络错误、5xx/429 响应和 JSON 解析错误视为可重试
+pub fn is_retryable(error: &anyhow::Error) -> bool {
+    error.chain().any(|cause| {
+        if let Some(e) = cause.downcast_ref::<reqwest::Error>() {
+            return match e.status() {
+                Some(status) => status.is_server_error() || status.as_u16() == 429,
+                None => e.is_timeout() || e.is_connect() || e.is_request() || e.is_body() || e.is_decode(),
+            };
+        }
+        cause.is::<serde_json::Error>()
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    #[tokio::test]
+    async fn test_retry_only_retryable_errors() {
+        let policy = RetryPolicy {
+            max_retries: 3,
+            base_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(5),
+        };
+
+        // JSON 错误重试后成功
+        let calls = AtomicU32::new(0);
+        let result = policy
+            .run(|| async {
+                if calls.fetch_add(1, Ordering::SeqCst) < 2 {
+                    Err(anyhow::Error::new(serde_json::from_str::<u32>("<html>").unwrap_err()))
+                } else {
+                    Ok(42)
+                }
+            })
+            .await;
+        assert_eq!(result.unwrap(), 42);
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+        // 其他错误不重试
+        let calls = AtomicU32::new(0);
+        let result: Result<()> = policy
+            .run(|| async {
+                calls.fetch_add(1, Ordering::SeqCst);
+                Err(anyhow!("not found in dictionary"))
+            })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        assert_eq!(parse_rate("0.5").unwrap(), 0.5);
+        for value in ["0", "-1", "inf", "NaN", "fast"] {
+            assert!(parse_rate(value).is_err(), "{}", value);
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use anyhow::{anyhow, Context, Result};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// 语音识别后端
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    /// 调用 whisper-cli 子进程
+    WhisperCli,
+    /// 进程内 whisper-rs（实验性，需要启用 whisper-rs feature 编译）
+    WhisperRs,
+}
+
+/// 在模型目录中查找模型：指定的模型不存在时使用目录中唯一的 .bin 文件
+pub fn resolve_model_file(models_dir: &Path, model_name: &str) -> Result<PathBuf> {
+    let target_path = models_dir.join(model_name);
+    if target_path.is_file() {
+        return Ok(target_path);
+    }
+
+    let mut model_files = Vec::new();
+    for entry in fs::read_dir(models_dir).context(format!("Failed to read models directory: {}", models_dir.display()))? {
+        let entry = entry.context("Failed to read directory entry")?;
+        let path = entry.path();
+        if path.is_file() && path.extension().is_some_and(|extension| extension == "bin") {
+            model_files.push(path);
+        }
+    }
+    model_files.sort();
+
+    match model_files.len() {
+        0 => Err(anyhow!("No .bin model files found in models directory: {}", models_dir.display())),
+        1 => Ok(model_files.remove(0)),
+        _ => Err(anyhow!("Multiple model files found in models directory. Please specify model name with --model-name or keep only one file: {:?}", model_files)),
+    }
+}
+
+/// 按当前系统选择安装提示
+pub fn platform_hint(linux: &str, macos: &str, windows: &str) -> String {
+    if cfg!(target_os = "windows") {
+        windows.to_string()
+    } else if cfg!(target_os = "macos") {
+        macos.to_string()
+    } else {
+        linux.to_string()
+    }
+}
+
+/// ffmpeg 的安装提示
+pub fn ffmpeg_install_hint() -> String {
+    platform_hint(
+        "Debian/Ubuntu: sudo apt install ffmpeg\n\
+         Fedora: sudo dnf install ffmpeg-free（完整版需先启用 RPM Fusion，再 sudo dnf install ffmpeg --allowerasing）\n\
+         Arch: sudo pacman -S ffmpeg\n\
+         也可以下载静态构建: https://johnvansickle.com/ffmpeg/",
+        "brew install ffmpeg",
+        "winget install ffmpeg 或 choco install ffmpeg，安装后重新打开终端\n\
+         也可以从 https://ffmpeg.org/download.html 下载并把 bin 目录加入 PATH",
+    )
+}
+
+/// whisper-cli 的安装提示
+pub fn whisper_cli_install_hint() -> String {
+    platform_hint(
+        "git clone https://github.com/ggerganov/whisper.cpp && cd whisper.cpp\n\
+         cmake -B build && cmake --build build -j --config Release\n\
+         cp build/bin/whisper-cli ~/.local/bin/（或用 --whisper-cli 指定完整路径）",
+        "brew install whisper-cpp",
+        "从 https://github.com/ggerganov/whisper.cpp/releases 下载 whisper-bin-x64.zip，\n\
+         解压后把目录加入 PATH，或用 --whisper-cli 指定 whisper-cli.exe 的完整路径",
+    )
+}
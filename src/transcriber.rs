@@ -0,0 +1,281 @@
+use anyhow::{anyhow, Context, Result};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use crate::toolchain::whisper_cli_install_hint;
+use crate::segment::{parse_srt, Segment};
+
+/// 识别参数，对应 whisper.cpp 的同名选项
+#[derive(Debug, Clone)]
+pub struct TranscribeOptions {
+    /// 识别语言，`auto` 为自动检测
+    pub language: String,
+    pub threads: usize,
+    pub use_gpu: bool,
+    pub gpu_device: u32,
+    pub beam_size: u32,
+    pub best_of: u32,
+    pub temperature: f32,
+    pub initial_prompt: Option<String>,
+    /// 是否让 whisper 直接翻译为英文（会丢失原始非英文内容）
+    pub translate: bool,
+}
+
+impl Default for TranscribeOptions {
+    fn default() -> Self {
+        Self {
+            language: "auto".to_string(),
+            threads: std::thread::available_parallelism().map(|n| n.get()).unwrap_or(4),
+            use_gpu: false,
+            gpu_device: 0,
+            beam_size: 8,
+            best_of: 1,
+            temperature: 0.0,
+            initial_prompt: None,
+            translate: false,
+        }
+    }
+}
+
+/// 语音识别后端
+pub trait Transcriber: Send + Sync {
+    /// 后端名称，用于日志输出
+    fn name(&self) -> &str;
+
+    /// 识别16kHz单声道WAV，`output_dir` 用于存放中间文件
+    fn transcribe(&self, audio_path: &Path, output_dir: &Path) -> Result<Vec<Segment>>;
+}
+
+/// 调用 whisper.cpp 的 whisper-cli 子进程进行识别
+pub struct WhisperCliTranscriber {
+    binary: String,
+    model_path: PathBuf,
+    options: TranscribeOptions,
+}
+
+impl WhisperCliTranscriber {
+    pub fn new(binary: impl Into<String>, model_path: PathBuf, options: TranscribeOptions) -> Self {
+        Self {
+            binary: binary.into(),
+            model_path,
+            options,
+        }
+    }
+
+    /// 检查 whisper-cli 是否在 PATH 中
+    pub fn check_available(&self) -> Result<PathBuf> {
+        which::which(&self.binary).map_err(|_| {
+            anyhow!(
+                "{} not found in PATH. Please install whisper-cli:\n{}\n\
+                 Or use --whisper-cli to point at the binary",
+                self.binary,
+                whisper_cli_install_hint()
+            )
+        })
+    }
+
+    /// whisper-cli 命令行：识别 `audio_path`，把 SRT 写到 `<raw_output>.srt`
+    fn command(&self, audio_path: &Path, raw_output: &Path) -> Command {
+        let options = &self.options;
+        let mut cmd = Command::new(&self.binary);
+        cmd.arg("-m").arg(&self.model_path)
+           .arg("-f").arg(audio_path)
+           .arg("-l").arg(&options.language)
+           .arg("-t").arg(options.threads.to_string())
+           .arg("-bs").arg(options.beam_size.to_string())
+           .arg("-bo").arg(options.best_of.to_string())
+           .arg("-tp").arg(options.temperature.to_string())
+           .arg("-osrt")         // 输出带时间戳的SRT格式
+           .arg("-of").arg(raw_output);
+
+        if options.translate {
+            cmd.arg("-tr");
+        }
+        if let Some(prompt) = &options.initial_prompt {
+            cmd.arg("--prompt").arg(prompt);
+        }
+        if options.use_gpu {
+            cmd.arg("-dev").arg(options.gpu_device.to_string());
+        } else {
+            cmd.arg("-ng");
+        }
+        cmd
+    }
+}
+
+impl Transcriber for WhisperCliTranscriber {
+    fn name(&self) -> &str {
+        &self.binary
+    }
+
+    fn transcribe(&self, audio_path: &Path, output_dir: &Path) -> Result<Vec<Segment>> {
+        self.check_available()?;
+
+        let output_name = audio_path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+        let raw_output = output_dir.join(format!("{}_raw", output_name));
+
+        let mut cmd = self.command(audio_path, &raw_output);
+        println!("🎯 Running whisper-cli with command: {:?}", cmd);
+
+        let output = cmd.output()
+            .context(format!("Failed to execute {}", self.binary))?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            return Err(anyhow!("whisper-cli failed:\nSTDERR: {}\nSTDOUT: {}", stderr, stdout));
+        }
+
+        // 读取生成的SRT文件（whisper-cli会自动添加.srt扩展名）
+        let srt_output = output_dir.join(format!("{}_raw.srt", output_name));
+        let srt_content = fs::read_to_string(&srt_output)
+            .context(format!("Failed to read generated SRT file: {}", srt_output.display()))?;
+
+        // 保留whisper-cli生成的中间SRT文件
+        println!("📄 保留中间字幕文件: {}", srt_output.display());
+
+        parse_srt(&srt_content)
+    }
+}
+
+/// 通过 whisper-rs 在进程内加载 GGML 模型进行识别
+#[cfg(feature = "whisper-rs")]
+pub struct WhisperRsTranscriber {
+    context: whisper_rs::WhisperContext,
+    options: TranscribeOptions,
+}
+
+#[cfg(feature = "whisper-rs")]
+impl WhisperRsTranscriber {
+    pub fn new(model_path: &Path, options: TranscribeOptions) -> Result<Self> {
+        let model = model_path
+            .to_str()
+            .ok_or_else(|| anyhow!("Invalid model path: {}", model_path.display()))?;
+
+        let mut context_params = whisper_rs::WhisperContextParameters::default();
+        context_params
+            .use_gpu(options.use_gpu)
+            .gpu_device(options.gpu_device as i32);
+        let context = whisper_rs::WhisperContext::new_with_params(model, context_params)
+            .map_err(|e| anyhow!("Failed to load model {}: {}", model_path.display(), e))?;
+
+        Ok(Self { context, options })
+    }
+}
+
+#[cfg(feature = "whisper-rs")]
+impl Transcriber for WhisperRsTranscriber {
+    fn name(&self) -> &str {
+        "whisper-rs"
+    }
+
+    fn transcribe(&self, audio_path: &Path, _output_dir: &Path) -> Result<Vec<Segment>> {
+        let samples = crate::audio::read_wav_samples(audio_path)?;
+
+        let options = &self.options;
+        let strategy = if options.beam_size > 1 {
+            whisper_rs::SamplingStrategy::BeamSearch {
+                beam_size: options.beam_size as i32,
+                patience: -1.0,
+            }
+        } else {
+            whisper_rs::SamplingStrategy::Greedy {
+                best_of: options.best_of as i32,
+            }
+        };
+        let mut params = whisper_rs::FullParams::new(strategy);
+        params.set_language(Some(options.language.as_str()));
+        params.set_translate(options.translate);
+        params.set_n_threads(options.threads as i32);
+        params.set_temperature(options.temperature);
+        if let Some(prompt) = &options.initial_prompt {
+            params.set_initial_prompt(prompt);
+        }
+        params.set_print_progress(false);
+        params.set_print_realtime(false);
+        params.set_print_special(false);
+        params.set_print_timestamps(false);
+
+        let mut state = self
+            .context
+            .create_state()
+            .map_err(|e| anyhow!("Failed to create whisper state: {}", e))?;
+        state
+            .full(params, &samples)
+            .map_err(|e| anyhow!("whisper-rs failed: {}", e))?;
+
+        let count = state
+            .full_n_segments()
+            .map_err(|e| anyhow!("Failed to read segment count: {}", e))?;
+        let mut segments = Vec::new();
+        for i in 0..count {
+            let text = state
+                .full_get_segment_text_lossy(i)
+                .map_err(|e| anyhow!("Failed to read segment text: {}", e))?;
+            // whisper 时间单位为 10ms
+            let t0 = state.full_get_segment_t0(i).map_err(|e| anyhow!("{}", e))?;
+            let t1 = state.full_get_segment_t1(i).map_err(|e| anyhow!("{}", e))?;
+
+            let text = text.trim();
+            if !text.is_empty() {
+                segments.push(Segment::new((t0 * 10) as u32, (t1 * 10) as u32, text));
+            }
+        }
+
+        Ok(segments)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn args(transcriber: &WhisperCliTranscriber) -> Vec<String> {
+        transcriber
+            .command(Path::new("out/ep01.wav"), Path::new("out/ep01_raw"))
+            .get_args()
+            .map(|arg| arg.to_string_lossy().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_whisper_cli_args() {
+        let options = TranscribeOptions {
+            threads: 4,
+            ..Default::default()
+        };
+        let transcriber = WhisperCliTranscriber::new("whisper-cli", PathBuf::from("models/ggml-large.bin"), options.clone());
+        assert_eq!(transcriber.command(Path::new("a.wav"), Path::new("a_raw")).get_program(), "whisper-cli");
+        assert_eq!(
+            args(&transcriber),
+            [
+                "-m", "models/ggml-large.bin", "-f", "out/ep01.wav", "-l", "auto", "-t", "4", "-bs", "8", "-bo", "1",
+                "-tp", "0", "-osrt", "-of", "out/ep01_raw", "-ng",
+            ]
+        );
+
+        // 翻译、提示词和 GPU 设备
+        let options = TranscribeOptions {
+            language: "ja".to_string(),
+            use_gpu: true,
+            gpu_device: 1,
+            beam_size: 5,
+            best_of: 2,
+            temperature: 0.2,
+            initial_prompt: Some("Hello, world.".to_string()),
+            translate: true,
+            ..options
+        };
+        let transcriber = WhisperCliTranscriber::new("/opt/whisper/main", PathBuf::from("ggml-base.bin"), options);
+        assert_eq!(
+            args(&transcriber),
+            [
+                "-m", "ggml-base.bin", "-f", "out/ep01.wav", "-l", "ja", "-t", "4", "-bs", "5", "-bo", "2", "-tp",
+                "0.2", "-osrt", "-of", "out/ep01_raw", "-tr", "--prompt", "Hello, world.", "-dev", "1",
+            ]
+        );
+    }
+}
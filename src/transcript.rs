@@ -0,0 +1,206 @@
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::segment::Segment;
+
+/// JSON 转写结果的格式版本，字段有不兼容变化时递增
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// 一个输入文件的识别结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Transcript {
+    pub schema_version: u32,
+    /// 原始输入文件
+    pub source: PathBuf,
+    /// 提取出的16kHz WAV
+    pub audio_path: PathBuf,
+    /// 从已有字幕导入时的字幕文件，语音识别得到的结果为 `None`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub imported_from: Option<PathBuf>,
+    pub segments: Vec<Segment>,
+}
+
+/// JSON-lines 的第一行：转写结果中segments以外的字段，没有segment时也能完整还原
+#[derive(Serialize, Deserialize)]
+struct JsonlHeader {
+    /// 固定为 `true`，用于和segment行区分
+    header: bool,
+    schema_version: u32,
+    source: PathBuf,
+    audio_path: PathBuf,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    imported_from: Option<PathBuf>,
+}
+
+/// JSON-lines 中的一行：每行一个segment，并带上来源信息
+#[derive(Serialize, Deserialize)]
+struct JsonlRecord {
+    schema_version: u32,
+    source: PathBuf,
+    audio_path: PathBuf,
+    #[serde(flatten)]
+    segment: Segment,
+}
+
+impl Transcript {
+    pub fn new(source: PathBuf, audio_path: PathBuf, segments: Vec<Segment>) -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            source,
+            audio_path,
+            imported_from: None,
+            segments,
+        }
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).context("Failed to serialize transcript")
+    }
+
+    pub fn to_jsonl(&self) -> Result<String> {
+        let header = JsonlHeader {
+            header: true,
+            schema_version: self.schema_version,
+            source: self.source.clone(),
+            audio_path: self.audio_path.clone(),
+            imported_from: self.imported_from.clone(),
+        };
+        let mut content = serde_json::to_string(&header).context("Failed to serialize transcript")?;
+        content.push('\n');
+        for segment in &self.segments {
+            let record = JsonlRecord {
+                schema_version: self.schema_version,
+                source: self.source.clone(),
+                audio_path: self.audio_path.clone(),
+                segment: segment.clone(),
+            };
+            content.push_str(&serde_json::to_string(&record).context("Failed to serialize transcript")?);
+            content.push('\n');
+        }
+        Ok(content)
+    }
+
+    pub fn from_json(content: &str) -> Result<Self> {
+        let transcript: Transcript = serde_json::from_str(content).context("Failed to parse transcript JSON")?;
+        check_schema_version(transcript.schema_version)?;
+        Ok(transcript)
+    }
+
+    /// 解析 JSON-lines；没有头部行的旧文件从第一个segment行取来源信息
+    pub fn from_jsonl(content: &str) -> Result<Self> {
+        let mut transcript: Option<Transcript> = None;
+        for (i, line) in content.lines().enumerate().filter(|(_, line)| !line.trim().is_empty()) {
+            let context = || format!("Failed to parse transcript JSON-lines at line {}", i + 1);
+            let value: serde_json::Value = serde_json::from_str(line).with_context(context)?;
+            if value.get("header") == Some(&serde_json::Value::Bool(true)) {
+                let header: JsonlHeader = serde_json::from_value(value).with_context(context)?;
+                check_schema_version(header.schema_version)?;
+                if transcript.is_some() {
+                    return Err(anyhow!("Unexpected transcript JSON-lines header at line {}", i + 1));
+                }
+                transcript = Some(Transcript {
+                    schema_version: header.schema_version,
+                    source: header.source,
+                    audio_path: header.audio_path,
+                    imported_from: header.imported_from,
+                    segments: Vec::new(),
+                });
+                continue;
+            }
+
+            let record: JsonlRecord = serde_json::from_value(value).with_context(context)?;
+            check_schema_version(record.schema_version)?;
+
+            transcript
+                .get_or_insert_with(|| Transcript::new(record.source.clone(), record.audio_path.clone(), Vec::new()))
+                .segments
+                .push(record.segment);
+        }
+        transcript.ok_or_else(|| anyhow!("Empty transcript JSON-lines without header"))
+    }
+
+    /// 读取 .json 或 .jsonl 转写文件
+    pub fn read(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .context(format!("Failed to read transcript: {}", path.display()))?;
+        let is_jsonl = path.extension().map(|ext| ext == "jsonl").unwrap_or(false);
+        if is_jsonl {
+            Self::from_jsonl(&content)
+        } else {
+            Self::from_json(&content)
+        }
+        .context(format!("Invalid transcript: {}", path.display()))
+    }
+}
+
+fn check_schema_version(version: u32) -> Result<()> {
+    if version > SCHEMA_VERSION {
+        return Err(anyhow!(
+            "Transcript schema version {} is newer than supported version {}",
+            version,
+            SCHEMA_VERSION
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_json_roundtrip() {
+        let mut segment = Segment::new(1200, 3450, "Take it easy.");
+        segment.language = Some("English".to_string());
+        segment.confidence = Some(0.93);
+        segment.is_target = true;
+        segment.translation = Some("放轻松".to_string());
+        let transcript = Transcript::new(
+            PathBuf::from("video2en_input/a.mp4"),
+            PathBuf::from("video2en_output/a.wav"),
+            vec![segment, Segment::new(3450, 5000, "放轻松")],
+        );
+
+        for parsed in [
+            Transcript::from_json(&transcript.to_json().unwrap()).unwrap(),
+            Transcript::from_jsonl(&transcript.to_jsonl().unwrap()).unwrap(),
+        ] {
+            assert_eq!(parsed.schema_version, SCHEMA_VERSION);
+            assert_eq!(parsed.source, transcript.source);
+            assert_eq!(parsed.segments.len(), 2);
+            assert_eq!(parsed.segments[0].end_ms, 3450);
+            assert_eq!(parsed.segments[0].translation.as_deref(), Some("放轻松"));
+            assert!(parsed.segments[0].is_target);
+            assert!(!parsed.segments[1].is_target);
+        }
+
+        let json = transcript.to_json().unwrap();
+        let current = format!("\"schema_version\": {}", SCHEMA_VERSION);
+        let newer = json.replace(&current, "\"schema_version\": 99");
+        assert!(Transcript::from_json(&newer).is_err());
+
+        // 导入来源和空的转写结果也能从 JSON-lines 还原，没有头部行的旧文件仍可读取
+        let mut empty = Transcript::new(transcript.source.clone(), transcript.audio_path.clone(), Vec::new());
+        empty.imported_from = Some(PathBuf::from("video2en_input/a.en.srt"));
+        for parsed in [
+            Transcript::from_json(&empty.to_json().unwrap()).unwrap(),
+            Transcript::from_jsonl(&empty.to_jsonl().unwrap()).unwrap(),
+        ] {
+            assert_eq!(parsed.source, empty.source);
+            assert_eq!(parsed.imported_from, empty.imported_from);
+            assert!(parsed.segments.is_empty());
+        }
+        let jsonl = transcript.to_jsonl().unwrap();
+        let legacy: String = jsonl.lines().skip(1).map(|line| format!("{}\n", line)).collect();
+        assert_eq!(Transcript::from_jsonl(&legacy).unwrap().segments.len(), 2);
+        assert!(Transcript::from_jsonl("").is_err());
+
+        // 版本 1 的 `is_english` 字段仍可读取
+        let older = json.replace(&current, "\"schema_version\": 1").replace("is_target", "is_english");
+        assert!(Transcript::from_json(&older).unwrap().segments[0].is_target);
+    }
+}
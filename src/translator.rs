@@ -0,0 +1,383 @@
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fs, path::{Path, PathBuf}, time::Duration};
+
+use crate::cache::CacheMode;
+use crate::text::normalize_text;
+use crate::throttle::RetryPolicy;
+use crate::youdao::YoudaoTranslator;
+use crate::youdao_translate::WordAllInfo;
+
+/// 与翻译服务无关的翻译结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Translation {
+    /// 整句翻译
+    pub translation: Option<String>,
+    #[serde(default)]
+    pub phonetics: Phonetics,
+    /// 词典释义
+    #[serde(default)]
+    pub senses: Vec<Sense>,
+    /// 双语例句
+    #[serde(default)]
+    pub examples: Vec<Example>,
+    /// 翻译服务返回的原始数据（有道为完整的 WordAllInfo）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Phonetics {
+    pub us: Option<String>,
+    pub uk: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Sense {
+    /// 词性
+    pub pos: Option<String>,
+    pub meaning: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Example {
+    pub source: String,
+    pub target: String,
+}
+
+/// 翻译服务
+#[async_trait]
+pub trait Translator: Send + Sync {
+    /// 服务名称，用于日志输出
+    fn name(&self) -> &str;
+
+    async fn translate(&self, text: &str) -> Result<Translation>;
+}
+
+/// 可选的翻译服务
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Provider {
+    /// 有道词典网页接口
+    Youdao,
+    /// LibreTranslate 兼容的 HTTP 接口
+    Libre,
+    /// 本地离线词典文件
+    Dict,
+}
+
+impl Provider {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Provider::Youdao => "youdao",
+            Provider::Libre => "libre",
+            Provider::Dict => "dict",
+        }
+    }
+}
+
+/// 翻译服务配置
+#[derive(Debug, Clone)]
+pub struct TranslatorConfig {
+    pub provider: Provider,
+    /// 覆盖服务地址，可指向本地替身服务
+    pub base_url: Option<String>,
+    pub api_key: Option<String>,
+    /// 离线词典文件路径（dict 服务使用）
+    pub dict_file: Option<PathBuf>,
+    pub source_lang: String,
+    pub target_lang: String,
+    pub cache_mode: CacheMode,
+    /// 缓存有效期，`None` 为永久有效
+    pub cache_ttl: Option<Duration>,
+    /// 同时进行的翻译请求数
+    pub concurrency: usize,
+    /// 每秒最多发起的请求数
+    pub rate_limit: f64,
+    pub retry: RetryPolicy,
+}
+
+impl Default for TranslatorConfig {
+    fn default() -> Self {
+        Self {
+            provider: Provider::Youdao,
+            base_url: None,
+            api_key: None,
+            dict_file: None,
+            source_lang: "en".to_string(),
+            target_lang: "zh".to_string(),
+            cache_mode: CacheMode::ReadWrite,
+            cache_ttl: None,
+            concurrency: 4,
+            rate_limit: 5.0,
+            retry: RetryPolicy::default(),
+        }
+    }
+}
+
+/// 根据配置创建翻译服务
+pub fn create_translator(config: &TranslatorConfig) -> Result<Box<dyn Translator>> {
+    match config.provider {
+        Provider::Youdao => {
+            let translator = match &config.base_url {
+                Some(url) => YoudaoTranslator::with_base_url(url.clone()),
+                None => YoudaoTranslator::new(),
+            };
+            Ok(Box::new(translator))
+        }
+        Provider::Libre => {
+            let base_url = config
+                .base_url
+                .clone()
+                .ok_or_else(|| anyhow!("LibreTranslate requires --translator-url"))?;
+            Ok(Box::new(LibreTranslator::new(
+                base_url,
+                config.api_key.clone(),
+                config.source_lang.clone(),
+                config.target_lang.clone(),
+            )))
+        }
+        Provider::Dict => {
+            let path = config
+                .dict_file
+                .as_ref()
+                .ok_or_else(|| anyhow!("Dictionary translator requires --dict-file"))?;
+            Ok(Box::new(DictTranslator::load(path)?))
+        }
+    }
+}
+
+#[async_trait]
+impl Translator for YoudaoTranslator {
+    fn name(&self) -> &str {
+        "youdao"
+    }
+
+    async fn translate(&self, text: &str) -> Result<Translation> {
+        let word_info = YoudaoTranslator::translate(self, &text.to_string()).await?;
+        let mut translation = Translation::from(&word_info);
+        translation.raw = serde_json::to_value(&word_info).ok();
+        Ok(translation)
+    }
+}
+
+impl From<&WordAllInfo> for Translation {
+    fn from(info: &WordAllInfo) -> Self {
+        let mut translation = Translation {
+            translation: info.fanyi.as_ref().map(|fanyi| fanyi.tran.clone()),
+            ..Default::default()
+        };
+
+        if let Some(ec) = &info.ec {
+            let word = &ec.word;
+            translation.phonetics = Phonetics {
+                us: Some(word.usphone.clone()).filter(|p| !p.is_empty()),
+                uk: Some(word.ukphone.clone()).filter(|p| !p.is_empty()),
+            };
+            translation.senses = word
+                .trs
+                .iter()
+                .map(|tr| Sense {
+                    pos: tr.pos.clone(),
+                    meaning: tr.tran.clone(),
+                })
+                .collect();
+        }
+
+        if let Some(blng) = &info.blng_sents_part {
+            translation.examples = blng
+                .sentence_pair
+                .iter()
+                .map(|pair| Example {
+                    source: pair.sentence_eng.clone(),
+                    target: pair.sentence_translation.clone(),
+                })
+                .collect();
+        }
+
+        // 短语没有整句翻译时，用第一条释义代替
+        if translation.translation.is_none() {
+            translation.translation = translation.senses.first().map(|sense| sense.meaning.clone());
+        }
+
+        translation
+    }
+}
+
+/// LibreTranslate 兼容接口（`POST {base_url}/translate`）
+pub struct LibreTranslator {
+    client: reqwest::Client,
+    base_url: String,
+    api_key: Option<String>,
+    source_lang: String,
+    target_lang: String,
+}
+
+#[derive(Serialize)]
+struct LibreRequest<'a> {
+    q: &'a str,
+    source: &'a str,
+    target: &'a str,
+    format: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    api_key: Option<&'a str>,
+}
+
+#[derive(Deserialize)]
+struct LibreResponse {
+    #[serde(rename = "translatedText")]
+    translated_text: String,
+}
+
+impl LibreTranslator {
+    pub fn new(base_url: String, api_key: Option<String>, source_lang: String, target_lang: String) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            api_key,
+            source_lang,
+            target_lang,
+        }
+    }
+}
+
+#[async_trait]
+impl Translator for LibreTranslator {
+    fn name(&self) -> &str {
+        "libretranslate"
+    }
+
+    async fn translate(&self, text: &str) -> Result<Translation> {
+        let request = LibreRequest {
+            q: text,
+            source: &self.source_lang,
+            target: &self.target_lang,
+            format: "text",
+            api_key: self.api_key.as_deref(),
+        };
+
+        let response = self
+            .client
+            .post(format!("{}/translate", self.base_url))
+            .json(&request)
+            .send()
+            .await?;
+
+        // 保留 reqwest 的状态码错误，以便判断是否可重试
+        if let Err(e) = response.error_for_status_ref() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(anyhow::Error::new(e).context(format!("LibreTranslate error: {}", error_text)));
+        }
+
+        let response_text = response.text().await?;
+        let result: LibreResponse = serde_json::from_str(&response_text)
+            .map_err(|x| anyhow::Error::new(x).context(format!("{} json fail", text)))?;
+
+        Ok(Translation {
+            translation: Some(result.translated_text),
+            ..Default::default()
+        })
+    }
+}
+
+/// 离线词典：每行 `英文<TAB>翻译[<TAB>音标]`，`#` 开头为注释
+pub struct DictTranslator {
+    entries: HashMap<String, Translation>,
+}
+
+impl DictTranslator {
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .context(format!("Failed to read dictionary file: {}", path.display()))?;
+        Ok(Self::parse(&content))
+    }
+
+    pub fn parse(content: &str) -> Self {
+        let mut entries = HashMap::new();
+        for line in content.lines() {
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let mut fields = line.split('\t');
+            let (Some(text), Some(target)) = (fields.next(), fields.next()) else {
+                continue;
+            };
+            let phonetic = fields.next().map(|p| p.trim().to_string()).filter(|p| !p.is_empty());
+
+            entries.insert(
+                normalize_text(text),
+                Translation {
+                    translation: Some(target.trim().to_string()),
+                    phonetics: Phonetics { us: phonetic, uk: None },
+                    ..Default::default()
+                },
+            );
+        }
+
+        Self { entries }
+    }
+}
+
+#[async_trait]
+impl Translator for DictTranslator {
+    fn name(&self) -> &str {
+        "dict"
+    }
+
+    async fn translate(&self, text: &str) -> Result<Translation> {
+        self.entries
+            .get(&normalize_text(text))
+            .cloned()
+            .ok_or_else(|| anyhow!("{} not found in dictionary", text))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    /// 启动一个只返回固定JSON的本地HTTP服务，返回其地址
+    async fn serve_json(body: &'static str) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut buf = vec![0u8; 8192];
+                let _ = stream.read(&mut buf).await;
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                let _ = stream.write_all(response.as_bytes()).await;
+            }
+        });
+        format!("http://{}", addr)
+    }
+
+    #[tokio::test]
+    async fn test_stand_in_providers() {
+        let url = serve_json(r#"{"translatedText": "放轻松"}"#).await;
+        let libre = LibreTranslator::new(url, None, "en".into(), "zh".into());
+        let result = libre.translate("Take it easy").await.unwrap();
+        assert_eq!(result.translation.as_deref(), Some("放轻松"));
+
+        let url = serve_json(
+            r#"{"lang": "eng", "input": "Take it easy", "le": "en",
+                "meta": {"input": "Take it easy", "guessLanguage": "eng", "isHasSimpleDict": "1",
+                         "le": "en", "lang": "eng", "dicts": []},
+                "fanyi": {"input": "Take it easy", "type": "2", "tran": "别紧张"}}"#,
+        )
+        .await;
+        let youdao: Box<dyn Translator> = Box::new(YoudaoTranslator::with_base_url(url));
+        let result = youdao.translate("Take it easy").await.unwrap();
+        assert_eq!(result.translation.as_deref(), Some("别紧张"));
+
+        let dict = DictTranslator::parse("# comment\nTake it easy.\t放轻松\t/teɪk/\n");
+        let result = dict.translate("take it easy").await.unwrap();
+        assert_eq!(result.translation.as_deref(), Some("放轻松"));
+        assert!(dict.translate("unknown").await.is_err());
+    }
+}
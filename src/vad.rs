@@ -0,0 +1,288 @@
+use anyhow::{Context, Result};
+use std::path::Path;
+
+use crate::audio::{read_wav_samples, write_wav_samples, WHISPER_SAMPLE_RATE};
+use crate::segment::Segment;
+use crate::transcriber::Transcriber;
+
+/// 基于能量的语音活动检测参数
+#[derive(Debug, Clone)]
+pub struct VadOptions {
+    /// 分析帧长
+    pub frame_ms: u32,
+    /// 帧能量（dBFS）高于该值视为语音
+    pub threshold_db: f32,
+    /// 短于该时长的语音段视为噪声丢弃
+    pub min_speech_ms: u32,
+    /// 语音段之间的静音短于该时长时合并为一段
+    pub min_silence_ms: u32,
+    /// 每段前后额外保留的时长，避免切掉词首词尾
+    pub padding_ms: u32,
+    /// 单段最长时长，超过时在最安静的帧处切开
+    pub max_chunk_ms: u32,
+}
+
+impl Default for VadOptions {
+    fn default() -> Self {
+        Self {
+            frame_ms: 30,
+            threshold_db: -40.0,
+            min_speech_ms: 250,
+            min_silence_ms: 700,
+            padding_ms: 200,
+            max_chunk_ms: 10 * 60 * 1000,
+        }
+    }
+}
+
+/// 一段音频在原始音频中的位置
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AudioChunk {
+    pub start_ms: u32,
+    pub end_ms: u32,
+}
+
+/// 检测16kHz单声道采样中的语音段，纯静音部分不会出现在结果中
+pub fn detect_speech(samples: &[f32], options: &VadOptions) -> Vec<AudioChunk> {
+    // 毫秒计算都按 u64 或饱和运算进行，接近 u32 上限的参数不会溢出
+    let frame_len = (WHISPER_SAMPLE_RATE as u64 * options.frame_ms as u64 / 1000).max(1) as usize;
+    let energies: Vec<f32> = samples.chunks(frame_len).map(frame_db).collect();
+    let total_ms = (samples.len() as u64 * 1000 / WHISPER_SAMPLE_RATE as u64).min(u32::MAX as u64) as u32;
+    let frame_ms = options.frame_ms.max(1);
+    let to_ms = |frame: usize| (frame as u64 * frame_ms as u64).min(u32::MAX as u64) as u32;
+
+    // 连续的语音帧组成语音段
+    let mut runs: Vec<(usize, usize)> = Vec::new();
+    let mut start = None;
+    for (i, &energy) in energies.iter().enumerate() {
+        match (energy >= options.threshold_db, start) {
+            (true, None) => start = Some(i),
+            (false, Some(s)) => {
+                runs.push((s, i));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(s) = start {
+        runs.push((s, energies.len()));
+    }
+
+    // 合并间隔很短的语音段，丢弃过短的语音段
+    let min_gap = (options.min_silence_ms / frame_ms) as usize;
+    let mut merged: Vec<(usize, usize)> = Vec::new();
+    for run in runs {
+        match merged.last_mut() {
+            Some(last) if run.0 - last.1 < min_gap => last.1 = run.1,
+            _ => merged.push(run),
+        }
+    }
+    let min_frames = (options.min_speech_ms / frame_ms).max(1) as usize;
+    merged.retain(|&(s, e)| e - s >= min_frames);
+
+    // 过长的语音段在最安静的帧处切开
+    let max_frames = ((options.max_chunk_ms / frame_ms) as usize).max(min_frames.saturating_mul(2));
+    let mut split = Vec::new();
+    for (mut s, e) in merged {
+        while e - s > max_frames {
+            // 在后半段中找能量最低的帧
+            let search = s + max_frames / 2..s + max_frames;
+            let cut = search
+                .min_by(|&a, &b| energies[a].total_cmp(&energies[b]))
+                .unwrap_or(s + max_frames);
+            split.push((s, cut));
+            s = cut;
+        }
+        split.push((s, e));
+    }
+
+    // 转为毫秒并加上前后留白；留白重叠的相邻段合并
+    let mut chunks: Vec<AudioChunk> = Vec::new();
+    for (s, e) in split {
+        let start_ms = to_ms(s).saturating_sub(options.padding_ms);
+        let end_ms = to_ms(e).saturating_add(options.padding_ms).min(total_ms);
+        match chunks.last_mut() {
+            Some(last) if start_ms <= last.end_ms && end_ms.saturating_sub(last.start_ms) <= options.max_chunk_ms => {
+                last.end_ms = end_ms
+            }
+            Some(last) if start_ms < last.end_ms => {
+                // 强制切开的段之间留白不重叠
+                let middle = ((start_ms as u64 + last.end_ms as u64) / 2) as u32;
+                last.end_ms = middle;
+                chunks.push(AudioChunk { start_ms: middle, end_ms });
+            }
+            _ => chunks.push(AudioChunk { start_ms, end_ms }),
+        }
+    }
+    chunks
+}
+
+/// 一帧的 RMS 能量（dBFS）
+fn frame_db(frame: &[f32]) -> f32 {
+    let mean_square = frame.iter().map(|s| s * s).sum::<f32>() / frame.len().max(1) as f32;
+    10.0 * mean_square.max(1e-10).log10()
+}
+
+/// 先做语音活动检测，只把语音段交给内部后端识别，再按各段在原始音频中的位置拼回时间轴
+pub struct VadTranscriber {
+    inner: Box<dyn Transcriber>,
+    options: VadOptions,
+}
+
+impl VadTranscriber {
+    pub fn new(inner: Box<dyn Transcriber>, options: VadOptions) -> Self {
+        Self { inner, options }
+    }
+}
+
+impl Transcriber for VadTranscriber {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn transcribe(&self, audio_path: &Path, output_dir: &Path) -> Result<Vec<Segment>> {
+        let samples = read_wav_samples(audio_path)?;
+        let chunks = detect_speech(&samples, &self.options);
+
+        let total_ms = samples.len() as u64 * 1000 / WHISPER_SAMPLE_RATE as u64;
+        let speech_ms: u64 = chunks.iter().map(|c| (c.end_ms - c.start_ms) as u64).sum();
+        println!(
+            "🔇 VAD: {} speech chunks, {:.1} of {:.1} minutes contain speech",
+            chunks.len(),
+            speech_ms as f64 / 60000.0,
+            total_ms as f64 / 60000.0
+        );
+
+        // 分段音频放在临时目录中，识别完成后自动删除
+        let stem = audio_path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+        let chunk_dir = tempfile::Builder::new()
+            .prefix(&format!("{}.vad.", stem))
+            .tempdir_in(output_dir)
+            .context("Failed to create VAD chunk directory")?;
+
+        let mut segments = Vec::new();
+        for (i, chunk) in chunks.iter().enumerate() {
+            let chunk_path = chunk_dir.path().join(format!("{}_{:04}.wav", stem, i + 1));
+            write_wav_samples(&chunk_path, chunk_samples(&samples, chunk), WHISPER_SAMPLE_RATE)?;
+
+            println!("🎙️ Chunk {}/{}: {} ms - {} ms", i + 1, chunks.len(), chunk.start_ms, chunk.end_ms);
+            let chunk_segments = self
+                .inner
+                .transcribe(&chunk_path, chunk_dir.path())
+                .context(format!("Failed to transcribe chunk {} of {}", i + 1, audio_path.display()))?;
+            segments.extend(offset_segments(chunk_segments, chunk));
+        }
+
+        Ok(segments)
+    }
+}
+
+/// 分段对应的采样
+pub fn chunk_samples<'a>(samples: &'a [f32], chunk: &AudioChunk) -> &'a [f32] {
+    let to_index = |ms: u32| (ms as u64 * WHISPER_SAMPLE_RATE as u64 / 1000).min(samples.len() as u64) as usize;
+    &samples[to_index(chunk.start_ms)..to_index(chunk.end_ms)]
+}
+
+/// 把分段内的时间换算为原始音频中的时间，超出分段的部分截断
+pub fn offset_segments(segments: Vec<Segment>, chunk: &AudioChunk) -> Vec<Segment> {
+    segments
+        .into_iter()
+        .map(|mut segment| {
+            segment.start_ms = chunk.start_ms.saturating_add(segment.start_ms).min(chunk.end_ms);
+            segment.end_ms = chunk.start_ms.saturating_add(segment.end_ms).min(chunk.end_ms);
+            segment
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn tone(ms: u32) -> Vec<f32> {
+        let count = (WHISPER_SAMPLE_RATE * ms / 1000) as usize;
+        (0..count).map(|i| 0.3 * (i as f32 * 0.1).sin()).collect()
+    }
+
+    fn silence(ms: u32) -> Vec<f32> {
+        vec![0.0; (WHISPER_SAMPLE_RATE * ms / 1000) as usize]
+    }
+
+    struct OneSegmentPerChunk;
+
+    impl Transcriber for OneSegmentPerChunk {
+        fn name(&self) -> &str {
+            "test"
+        }
+
+        fn transcribe(&self, audio_path: &Path, _output_dir: &Path) -> Result<Vec<Segment>> {
+            let samples = read_wav_samples(audio_path)?;
+            let duration = (samples.len() as u64 * 1000 / WHISPER_SAMPLE_RATE as u64) as u32;
+            Ok(vec![Segment::new(0, duration + 500, "hello")])
+        }
+    }
+
+    #[test]
+    fn test_vad_chunks_and_offsets() {
+        // 语音 1s，短停顿 0.3s，语音 0.6s，长静音 5s，噪声 0.09s，静音 1s，语音 1s
+        let samples = [
+            silence(2000),
+            tone(1000),
+            silence(300),
+            tone(600),
+            silence(5000),
+            tone(90),
+            silence(1000),
+            tone(1020),
+            silence(900),
+        ]
+        .concat();
+
+        let options = VadOptions::default();
+        let chunks = detect_speech(&samples, &options);
+        assert_eq!(
+            chunks,
+            vec![
+                AudioChunk { start_ms: 1780, end_ms: 4100 },
+                AudioChunk { start_ms: 9790, end_ms: 11210 },
+            ]
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        let audio_path = dir.path().join("lecture.wav");
+        write_wav_samples(&audio_path, &samples, WHISPER_SAMPLE_RATE).unwrap();
+
+        let transcriber = VadTranscriber::new(Box::new(OneSegmentPerChunk), options);
+        let segments = transcriber.transcribe(&audio_path, dir.path()).unwrap();
+        let times: Vec<(u32, u32)> = segments.iter().map(|s| (s.start_ms, s.end_ms)).collect();
+        assert_eq!(times, vec![(1780, 4100), (9790, 11210)]);
+
+        // 临时分段目录已删除
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_vad_near_u32_max() {
+        let samples = [silence(500), tone(1000), silence(500)].concat();
+        let options = VadOptions {
+            min_silence_ms: u32::MAX,
+            padding_ms: u32::MAX,
+            max_chunk_ms: u32::MAX,
+            ..Default::default()
+        };
+        assert_eq!(detect_speech(&samples, &options), vec![AudioChunk { start_ms: 0, end_ms: 2000 }]);
+
+        let options = VadOptions {
+            frame_ms: u32::MAX,
+            min_speech_ms: u32::MAX,
+            ..Default::default()
+        };
+        assert_eq!(detect_speech(&samples, &options), vec![AudioChunk { start_ms: 0, end_ms: 2000 }]);
+
+        // 换算到原始时间时饱和，并截断到分段结尾
+        let chunk = AudioChunk { start_ms: u32::MAX - 1000, end_ms: u32::MAX };
+        let segments = offset_segments(vec![Segment::new(500, u32::MAX, "end")], &chunk);
+        assert_eq!((segments[0].start_ms, segments[0].end_ms), (u32::MAX - 500, u32::MAX));
+        assert!(chunk_samples(&samples, &chunk).is_empty());
+    }
+}
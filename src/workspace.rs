@@ -0,0 +1,305 @@
+use anyhow::{anyhow, Context, Result};
+use clap::{Command, ValueEnum};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// workspace 根目录中的配置文件名
+pub const CONFIG_FILE_NAME: &str = "video2en.toml";
+
+/// txt2audio 默认的 TTS 服务地址
+pub const DEFAULT_TTS_URL: &str = "http://localhost:5000";
+
+/// `video2en init` 写出的示例配置，取值与内置默认值一致
+pub const SAMPLE_CONFIG: &str = r#"# video2en / txt2audio 工作区配置，命令行参数优先于这里的设置
+# 使用 --print-config 查看合并命令行参数后实际生效的配置
+
+# 子目录，相对路径相对于工作区根目录，也可以使用绝对路径
+[dirs]
+video2en_input = "video2en_input"
+models = "models"
+video2en_output = "video2en_output"
+cache = "video2en_cache"
+txt2audio_input = "txt2audio_input"
+txt2audio_output = "txt2audio_output"
+
+[video2en]
+backend = "whisper-cli"
+# models 目录中的模型文件名
+model = "ggml-large.bin"
+# 识别语言: auto / en / zh ...
+language = "auto"
+# 要保留的目标语言
+filter_language = "en"
+candidate_languages = ["en", "zh"]
+# outputs = ["en-subs", "unique-txt", "bilingual-subs", "json"]
+subtitle_formats = ["srt", "vtt"]
+
+[translation]
+# youdao / libre / dict
+provider = "youdao"
+# url = "http://localhost:5050"
+target_lang = "zh"
+
+[tts]
+url = "http://localhost:5000"
+language = "en"
+male_speaker_wav = "1320-122617-0037.wav"
+female_speaker_wav = "en_sample.wav"
+"#;
+
+/// workspace 根目录中的 `video2en.toml`，`video2en` 和 `txt2audio` 共用；
+/// 其中的值作为命令行参数的默认值，命令行中给出的参数优先
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WorkspaceConfig {
+    pub dirs: WorkspaceDirs,
+    pub video2en: Video2EnSettings,
+    pub translation: TranslationSettings,
+    pub tts: TtsSettings,
+}
+
+/// workspace 中的子目录，相对路径相对于 workspace 根目录
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WorkspaceDirs {
+    pub video2en_input: PathBuf,
+    pub models: PathBuf,
+    pub video2en_output: PathBuf,
+    /// 翻译缓存和全局句子索引
+    pub cache: PathBuf,
+    pub txt2audio_input: PathBuf,
+    pub txt2audio_output: PathBuf,
+}
+
+impl Default for WorkspaceDirs {
+    fn default() -> Self {
+        Self {
+            video2en_input: PathBuf::from("video2en_input"),
+            models: PathBuf::from("models"),
+            video2en_output: PathBuf::from("video2en_output"),
+            cache: PathBuf::from("video2en_cache"),
+            txt2audio_input: PathBuf::from("txt2audio_input"),
+            txt2audio_output: PathBuf::from("txt2audio_output"),
+        }
+    }
+}
+
+/// `video2en` 的识别和输出设置，对应同名命令行参数
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Video2EnSettings {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub backend: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub whisper_cli: Option<String>,
+    /// models 目录中的模型文件名
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// 识别语言
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// 要保留的目标语言
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filter_language: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub candidate_languages: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub outputs: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subtitle_formats: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bilingual_layout: Option<String>,
+}
+
+/// 翻译设置
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TranslationSettings {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target_lang: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dict_file: Option<PathBuf>,
+}
+
+/// `txt2audio` 的 TTS 设置
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TtsSettings {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub male_speaker_wav: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub female_speaker_wav: Option<String>,
+}
+
+impl WorkspaceConfig {
+    pub fn path(workspace: &Path) -> PathBuf {
+        workspace.join(CONFIG_FILE_NAME)
+    }
+
+    /// 读取 workspace 中的配置文件，不存在时使用默认配置
+    pub fn load(workspace: &Path) -> Result<Self> {
+        let path = Self::path(workspace);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(&path).context(format!("Failed to read config: {}", path.display()))?;
+        toml::from_str(&content).context(format!("Invalid config: {}", path.display()))
+    }
+
+    pub fn to_toml(&self) -> Result<String> {
+        toml::to_string(self).context("Failed to serialize config")
+    }
+}
+
+/// 创建 workspace 的子目录和示例配置，已存在的目录和配置文件保持不变；返回新创建的路径
+pub fn init_workspace(workspace: &Path) -> Result<Vec<PathBuf>> {
+    let mut created = Vec::new();
+    if !workspace.exists() {
+        fs::create_dir_all(workspace).context(format!("Failed to create workspace: {}", workspace.display()))?;
+        created.push(workspace.to_path_buf());
+    }
+
+    let config_path = WorkspaceConfig::path(workspace);
+    if !config_path.exists() {
+        fs::write(&config_path, SAMPLE_CONFIG).context(format!("Failed to write {}", config_path.display()))?;
+        created.push(config_path);
+    }
+
+    // 已有配置文件时按其中的目录名创建
+    let dirs = WorkspaceConfig::load(workspace)?.dirs;
+    for dir in [
+        &dirs.video2en_input,
+        &dirs.models,
+        &dirs.video2en_output,
+        &dirs.cache,
+        &dirs.txt2audio_input,
+        &dirs.txt2audio_output,
+    ] {
+        let path = workspace.join(dir);
+        if !path.exists() {
+            fs::create_dir_all(&path).context(format!("Failed to create directory: {}", path.display()))?;
+            created.push(path);
+        }
+    }
+    Ok(created)
+}
+
+/// 把配置文件中的值设为对应命令行参数的默认值，命令行中给出的参数仍然优先。
+/// `defaults` 为（参数 id，值）列表，没有值的参数保持原来的默认值
+pub fn with_config_defaults(mut command: Command, defaults: Vec<(&'static str, Vec<String>)>) -> Command {
+    for (id, values) in defaults {
+        if !values.is_empty() {
+            command = command.mut_arg(id, |arg| arg.default_values(values));
+        }
+    }
+    command
+}
+
+/// 每个输入文件的结果写在以去掉扩展名的文件名命名的子目录中，
+/// `ep01.mp4` 和 `ep01.mkv` 这样只有扩展名不同的输入会写到同一个目录，处理前拒绝
+pub fn check_output_folders(input_files: &[PathBuf]) -> Result<()> {
+    let mut folders: BTreeMap<String, &Path> = BTreeMap::new();
+    for file in input_files {
+        let stem = file.file_stem().unwrap_or_default().to_string_lossy().to_string();
+        if let Some(other) = folders.insert(stem.clone(), file) {
+            return Err(anyhow!(
+                "Input files {} and {} would share the output folder {}; rename one of them",
+                other.display(),
+                file.display(),
+                stem
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// 枚举参数在命令行中的写法，如 `whisper-cli`
+pub fn value_name<T: ValueEnum>(value: &T) -> String {
+    value
+        .to_possible_value()
+        .map(|value| value.get_name().to_string())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use clap::{Arg, ArgAction};
+
+    #[test]
+    fn test_check_output_folders() {
+        let files = |names: &[&str]| names.iter().map(PathBuf::from).collect::<Vec<_>>();
+        assert!(check_output_folders(&files(&["in/ep01.mp4", "in/ep02.mp4", "in/ep01.part2.mp4"])).is_ok());
+
+        let error = check_output_folders(&files(&["in/ep01.mkv", "in/ep01.mp4"])).unwrap_err().to_string();
+        assert!(error.contains("in/ep01.mkv and in/ep01.mp4 would share the output folder ep01"), "{}", error);
+    }
+
+    #[test]
+    fn test_workspace_config_defaults() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(WorkspaceConfig::load(dir.path()).unwrap(), WorkspaceConfig::default());
+
+        // init 创建子目录和示例配置，再次运行不覆盖已有文件
+        let workspace = dir.path().join("new");
+        let created = init_workspace(&workspace).unwrap();
+        assert_eq!(created.len(), 8);
+        assert!(workspace.join("models").is_dir());
+        assert_eq!(WorkspaceConfig::load(&workspace).unwrap().dirs, WorkspaceDirs::default());
+        assert!(init_workspace(&workspace).unwrap().is_empty());
+
+        fs::write(
+            WorkspaceConfig::path(dir.path()),
+            r#"
+[dirs]
+models = "/shared/models"
+
+[video2en]
+model = "ggml-small.en.bin"
+outputs = ["en-subs", "json"]
+
+[tts]
+url = "http://tts:5000"
+"#,
+        )
+        .unwrap();
+        let config = WorkspaceConfig::load(dir.path()).unwrap();
+        assert_eq!(config.dirs.models, PathBuf::from("/shared/models"));
+        assert_eq!(config.dirs.video2en_input, PathBuf::from("video2en_input"));
+        assert_eq!(config.tts.url.as_deref(), Some("http://tts:5000"));
+
+        // 配置文件中的值作为默认值，命令行参数优先
+        let command = Command::new("test")
+            .arg(Arg::new("model").long("model").default_value("ggml-large.bin"))
+            .arg(Arg::new("outputs").long("outputs").value_delimiter(',').action(ArgAction::Append));
+        let command = with_config_defaults(
+            command,
+            vec![
+                ("model", config.video2en.model.iter().cloned().collect()),
+                ("outputs", config.video2en.outputs.clone().unwrap_or_default()),
+            ],
+        );
+        let matches = command.clone().get_matches_from(["test"]);
+        assert_eq!(matches.get_one::<String>("model").unwrap(), "ggml-small.en.bin");
+        let outputs: Vec<&String> = matches.get_many("outputs").unwrap().collect();
+        assert_eq!(outputs, ["en-subs", "json"]);
+        let matches = command.get_matches_from(["test", "--model", "ggml-base.bin"]);
+        assert_eq!(matches.get_one::<String>("model").unwrap(), "ggml-base.bin");
+
+        // 未知的键报错，避免拼写错误被忽略
+        fs::write(WorkspaceConfig::path(dir.path()), "[video2en]\nmodle = \"x\"\n").unwrap();
+        assert!(WorkspaceConfig::load(dir.path()).is_err());
+    }
+}
@@ -1,14 +1,34 @@
-use anyhow::{anyhow, Result};
+use anyhow::Result;
 use std::str::FromStr;
 use url::Url;
 
 use crate::youdao_translate::WordAllInfo;
 
 
-pub struct YoudaoTranslator;
+/// 有道词典网页接口默认地址
+pub const YOUDAO_BASE_URL: &str = "https://dict.youdao.com";
+
+pub struct YoudaoTranslator {
+    base_url: String,
+}
+
+impl Default for YoudaoTranslator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 impl YoudaoTranslator {
+    pub fn new() -> Self {
+        Self::with_base_url(YOUDAO_BASE_URL)
+    }
 
+    /// 使用自定义服务地址（例如本地替身服务）
+    pub fn with_base_url(base_url: impl Into<String>) -> Self {
+        Self {
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+        }
+    }
 
     pub async fn translate(&self, text: &String) -> Result<WordAllInfo> {
         let client = reqwest::Client::builder().no_proxy().build()?;
@@ -34,9 +54,10 @@ impl YoudaoTranslator {
             ("keyfrom", keyfrom),
         ];
         let response = client
-            .post(Url::from_str(
-                "https://dict.youdao.com/jsonapi_s?doctype=json&jsonversion=4",
-            )?)
+            .post(Url::from_str(&format!(
+                "{}/jsonapi_s?doctype=json&jsonversion=4",
+                self.base_url
+            ))?)
             .form(&params)
             .send()
             .await?;
@@ -44,7 +65,7 @@ impl YoudaoTranslator {
         let response_text = response.text().await?;
         
         serde_json::from_str::<WordAllInfo>(&response_text)
-            .map_err(|x| anyhow!("{} json fail: {}", text, x.to_string()))
+            .map_err(|x| anyhow::Error::new(x).context(format!("{} json fail", text)))
     }
 
 }